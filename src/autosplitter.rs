@@ -1,6 +1,13 @@
-use asr::{settings::{Gui, gui::Title}, time::Duration, timer, timer::TimerState};
+use asr::{settings::{Gui, gui::Title}, string::ArrayString, time::Duration, time_util::Instant, timer, timer::TimerState};
+use std::fmt::Write;
 
-use crate::game::GameAutoSplitter;
+use crate::game::{GameAutoSplitter, GameId, LoadStrategy, TimerVariable};
+
+#[cfg(test)]
+pub(crate) mod golden;
+
+/// Update loop frequency in Hz
+pub const TICK_RATE: f64 = 120.0;
 
 #[derive(Gui)]
 pub struct AutoSplitterSettings {
@@ -19,6 +26,143 @@ pub struct AutoSplitterSettings {
     /// Automatic resets are disabled after the first split even if splitting is disabled
     #[default = true]
     pub reset: bool,
+    /// Resume game time after a game swap that does not complete within
+    ///
+    /// Game time stays paused after completing a game until the next game starts
+    pub swap_timeout: SwapTimeout,
+    /// Games
+    pub _games_settings: Title,
+    /// Attach to Risk of Rain
+    #[default = true]
+    pub enable_ror1: bool,
+    /// Attach to Risk of Rain 2
+    #[default = true]
+    pub enable_ror2: bool,
+    /// Attach to Risk of Rain Returns
+    #[default = true]
+    pub enable_rorr: bool,
+    /// Game to attach to first when several games are running
+    pub attach_first: AttachFirst,
+    /// Debugging
+    pub _debug_settings: Title,
+    /// Print diagnostic messages to the runtime log
+    ///
+    /// Includes the build strings of unsupported game versions, which help adding support for them.
+    /// Also publishes a one-line "Status" variable and other debug variables
+    #[default = false]
+    pub debug_messages: bool,
+    /// Print diagnostics for the attached game
+    ///
+    /// Prints once when enabled, disable and enable again for another dump
+    #[default = false]
+    pub dump_diagnostics: bool,
+    /// Dry run: log start, split, reset, and completion decisions without acting on them
+    ///
+    /// The timer is never started, split, reset, or paused. Decisions are printed to the runtime log when they begin
+    #[default = false]
+    pub dry_run: bool,
+}
+
+#[derive(Gui, Clone, Copy, PartialEq, Eq)]
+pub enum SwapTimeout {
+    /// Never
+    Never,
+    /// 1 minute
+    OneMinute,
+    /// 5 minutes
+    #[default]
+    FiveMinutes,
+    /// 15 minutes
+    FifteenMinutes,
+}
+
+impl SwapTimeout {
+    /// Timeout in update loop ticks
+    fn ticks(self) -> Option<u32> {
+        let seconds = match self {
+            SwapTimeout::Never => return None,
+            SwapTimeout::OneMinute => 60.0,
+            SwapTimeout::FiveMinutes => 300.0,
+            SwapTimeout::FifteenMinutes => 900.0,
+        };
+        return Some((seconds * TICK_RATE) as u32);
+    }
+}
+
+#[derive(Gui, Clone, Copy, PartialEq, Eq)]
+pub enum AttachFirst {
+    /// Default order
+    #[default]
+    Default,
+    /// Risk of Rain
+    RiskOfRain,
+    /// Risk of Rain 2
+    RiskOfRain2,
+    /// Risk of Rain Returns
+    RiskOfRainReturns,
+}
+
+impl AttachFirst {
+    /// Game to attach to first, None keeps the default order
+    fn game(self) -> Option<GameId> {
+        return match self {
+            AttachFirst::Default => None,
+            AttachFirst::RiskOfRain => Some(GameId::RiskOfRain),
+            AttachFirst::RiskOfRain2 => Some(GameId::RiskOfRain2),
+            AttachFirst::RiskOfRainReturns => Some(GameId::RiskOfRainReturns),
+        };
+    }
+}
+
+/// Timer operation decided by the update loop
+///
+/// Collected during a tick and applied afterwards, so the decision logic never calls the runtime itself
+#[derive(Clone, Debug, PartialEq)]
+pub enum TimerCommand {
+    Start,
+    Split,
+    Reset,
+    PauseGameTime,
+    ResumeGameTime,
+    SetGameTime(Duration),
+}
+
+/// Mirror of the timer's game time, since the runtime can't read it back
+///
+/// Times are runtime clock readings, see `AutoSplitter::now()`
+#[derive(Clone, Copy)]
+pub struct GameClock {
+    /// Game time when `since` was read, or the whole game time while paused
+    banked: Duration,
+    /// Clock reading game time last resumed at, None while paused
+    since: Option<Duration>,
+}
+
+impl GameClock {
+    /// Game time starting from zero at `now`
+    fn started(now: Duration) -> Self { Self { banked: Duration::ZERO, since: Some(now) } }
+
+    fn current(&self, now: Duration) -> Duration {
+        return self.banked + self.since.map_or(Duration::ZERO, |since| now - since);
+    }
+
+    fn pause(&mut self, now: Duration) {
+        self.banked = self.current(now);
+        self.since = None;
+    }
+
+    fn resume(&mut self, now: Duration) {
+        if self.since.is_none() {
+            self.since = Some(now);
+        }
+    }
+
+    fn set(&mut self, time: Duration, now: Duration) {
+        self.banked = time;
+        if self.since.is_some() {
+            self.since = Some(now);
+        }
+    }
 }
 
 /// Timer state for update loop
@@ -26,41 +170,228 @@ pub struct AutoSplitterSettings {
 pub struct AutoSplitterState {
     /// For tracking timer pause between games
     pub switching_games: bool,
+    /// Ticks spent waiting for the next game to start
+    pub switching_ticks: u32,
+    /// Completed game swaps this run
+    pub swaps: u32,
+    /// Game the run last swapped to, None after a reset and while no game is attached
+    pub last_swap: Option<&'static str>,
     /// Avoids unwanted resets
     pub autoreset_lockout: bool,
     /// Prevents flodding the runtime with pause/resume commands
     pub was_loading: bool,
+    /// Game time as set by the autosplitter, None until the timer is seen running
+    pub game_clock: Option<GameClock>,
+    /// Game time banked by earlier games of the run, added to `GameAutoSplitter::game_time()`
+    ///
+    /// Captured when a game swap completes, so a game setting its own in-game time never overwrites earlier games
+    pub game_time_base: Duration,
 }
 
+/// Decisions logged in dry run mode, in the order they are evaluated
+const DRY_RUN_DECISIONS: [&str; 4] = ["reset", "complete", "split", "start"];
+
 pub struct AutoSplitter {
     settings: AutoSplitterSettings,
     state: AutoSplitterState,
+    /// Name of the game the last update came from
+    attached_game: Option<&'static str>,
+    swaps_var: TimerVariable<u32>,
+    last_swap_var: TimerVariable<&'static str>,
+    status_var: TimerVariable<ArrayString<96>>,
+    /// Last seen value of the momentary diagnostics dump setting
+    dump_diagnostics: bool,
+    /// Dry run decisions of the previous tick, in `DRY_RUN_DECISIONS` order
+    dry_run_decisions: [bool; 4],
+    /// Timer operations decided this tick, applied at the end of the tick
+    commands: Vec<TimerCommand>,
+    /// Messages printed at the end of the tick
+    messages: Vec<String>,
+    /// Reference point of `now()`, set on first use
+    epoch: Option<Instant>,
     //game_splitter: Option<&dyn GameAutoSplitter>, // ERROR something something not Send
 }
 
 impl AutoSplitter {
-    pub fn new() -> Self { Self { settings: AutoSplitterSettings::register(), state: AutoSplitterState::default() } }
+    pub fn new() -> Self {
+        timer::set_variable("Game", "No game attached");
+        return Self::with_settings(AutoSplitterSettings::register());
+    }
+
+    fn with_settings(settings: AutoSplitterSettings) -> Self {
+        Self {
+            settings,
+            state: AutoSplitterState::default(),
+            attached_game: None,
+            swaps_var: TimerVariable::new("Game Swaps"),
+            last_swap_var: TimerVariable::new("Last Game Swap"),
+            status_var: TimerVariable::new("Status"),
+            dump_diagnostics: false,
+            dry_run_decisions: [false; 4],
+            commands: Vec::new(),
+            messages: Vec::new(),
+            epoch: None,
+        }
+    }
 
     fn reset_state(&mut self) {
         self.state = AutoSplitterState::default();
     }
 
-    /// FIXME Dirty hack results in game time being marginally shorter than real time (<1ms)
-    fn initialize_game_time_workaround() {
-        timer::set_game_time(Duration::ZERO);
+    /// Splits without checking the game split conditions
+    ///
+    /// This bypasses the game predicates and settings, but not the state bookkeeping (disables autoresets)
+    pub fn force_split(&mut self) {
+        self.force_split_tick(timer::state());
+        self.apply_commands();
+    }
+
+    /// Resets without checking the game reset conditions
+    ///
+    /// This bypasses the game predicates, settings, and autoreset lockout, but still clears the timer state
+    /// and soft-resets the game like an automatic reset
+    pub fn force_reset(&mut self, game_splitter: Option<&mut dyn GameAutoSplitter>) {
+        self.force_reset_tick(game_splitter);
+        self.apply_commands();
+    }
+
+    /// Decides the commands of a forced split, without calling the runtime
+    fn force_split_tick(&mut self, timer_state: TimerState) {
+        if self.settings.dry_run {
+            self.messages.push(String::from("[dry run] would force a split"));
+        } else if let TimerState::Running | TimerState::Paused = timer_state {
+            self.split_timer();
+        }
+    }
+
+    /// Decides the commands of a forced reset, without calling the runtime
+    fn force_reset_tick(&mut self, game_splitter: Option<&mut dyn GameAutoSplitter>) {
+        if self.settings.dry_run {
+            self.messages.push(String::from("[dry run] would force a reset"));
+        } else {
+            self.reset_timer();
+            if let Some(game_splitter) = game_splitter {
+                game_splitter.soft_reset();
+            }
+        }
+    }
+
+    fn split_timer(&mut self) {
+        self.commands.push(TimerCommand::Split);
+        self.state.autoreset_lockout = true;
+    }
+
+    fn reset_timer(&mut self) {
+        self.commands.push(TimerCommand::Reset);
+        self.reset_state();
+    }
+
+    /// Diagnostic messages are enabled
+    pub fn debug(&self) -> bool {
+        return self.settings.debug_messages;
+    }
+
+    /// A diagnostics dump was requested since the last call
+    pub fn diagnostics_requested(&mut self) -> bool {
+        let requested = self.settings.dump_diagnostics && !self.dump_diagnostics;
+        self.dump_diagnostics = self.settings.dump_diagnostics;
+        return requested;
+    }
+
+    /// Indices into `games` of the enabled games, in the order they should be attached to
+    pub fn attach_order(&mut self, games: &[Box<dyn GameAutoSplitter>]) -> Vec<usize> {
+        self.settings.update();
+        let first = self.settings.attach_first.game();
+        let is_first = |game: &usize| Some(games[*game].id()) == first;
+        return (0..games.len()).filter(is_first)
+            .chain((0..games.len()).filter(|game| !is_first(game)))
+            .filter(|&game| self.game_enabled(games[game].id()))
+            .collect();
+    }
+
+    /// One-line summary of the autosplitter state, the load state is the one from the previous tick
+    fn status_line(&self, game_splitter: Option<&dyn GameAutoSplitter>) -> ArrayString<96> {
+        let yes_no = |value: bool| if value { "yes" } else { "no" };
+        let mut status = ArrayString::new();
+        // the longest possible line fits, a write error would only truncate the status
+        let _ = match game_splitter {
+            Some(game_splitter) => {
+                let progress = game_splitter.progress();
+                write!(status, "{} | {} | {} | loading:{} | swap:{}",
+                    game_splitter.id().short_name(),
+                    if progress.is_some() { "in_run" } else { "menu" },
+                    progress.as_deref().unwrap_or("-"),
+                    yes_no(self.state.was_loading),
+                    yes_no(self.state.switching_games))
+            },
+            None => write!(status, "no game | loading:{} | swap:{}", yes_no(self.state.was_loading), yes_no(self.state.switching_games)),
+        };
+        return status;
+    }
+
+    /// Per-game enable toggle
+    fn game_enabled(&self, game: GameId) -> bool {
+        return match game {
+            GameId::RiskOfRain => self.settings.enable_ror1,
+            GameId::RiskOfRain2 => self.settings.enable_ror2,
+            GameId::RiskOfRainReturns => self.settings.enable_rorr,
+        };
+    }
+
+    /// Runtime clock, only differences between readings are meaningful
+    fn now(&mut self) -> Duration {
+        let epoch = *self.epoch.get_or_insert_with(Instant::now);
+        return epoch.elapsed();
     }
 
     /// Splitting logic update loop runs once per tick
-    pub(crate) fn update_loop(&mut self, game_splitter: Option<&dyn GameAutoSplitter>) {
+    ///
+    /// Per tick precedence while the timer runs: reset > completion > split.
+    /// When a game reports completion and a split on the same tick only the completion split fires,
+    /// and game swap handling starts (`switching_games`).
+    pub(crate) fn update_loop(&mut self, game_splitter: Option<&mut dyn GameAutoSplitter>) {
         self.settings.update();
 
+        self.update_attached_game(game_splitter.as_deref());
+        self.swaps_var.update(Some(self.state.swaps));
+        // e.g. "ror2 | in_run | stage 3 | loading:no | swap:no"
+        let status = self.debug().then(|| self.status_line(game_splitter.as_deref()));
+        self.status_var.update(status);
+
+        let now = self.now();
+        self.tick(timer::state(), now, game_splitter);
+        self.last_swap_var.update(self.state.last_swap);
+        self.apply_commands();
+    }
+
+    /// Decides this tick's timer commands from the timer state and the game, without calling the runtime
+    fn tick(&mut self, timer_state: TimerState, now: Duration, game_splitter: Option<&mut dyn GameAutoSplitter>) {
+        // Dry run, checked before any decision that could touch the timer
+        if self.settings.dry_run {
+            if let Some(game_splitter) = game_splitter {
+                self.log_decisions(game_splitter);
+            }
+            return;
+        }
+
+        // Give up on a game swap when the next game never starts
+        if self.state.switching_games {
+            self.state.switching_ticks += 1;
+            if self.settings.swap_timeout.ticks().is_some_and(|timeout| self.state.switching_ticks >= timeout) {
+                self.state.switching_games = false;
+                self.set_loading(false, now);
+            }
+        } else {
+            self.state.switching_ticks = 0;
+        }
+
         // Disconnected from all games
-        if game_splitter.is_none() {
-            match timer::state() {
+        let Some(game_splitter) = game_splitter else {
+            self.state.last_swap = None;
+            match timer_state {
                 TimerState::Running | TimerState::Paused => {
-                    if self.state.switching_games && !self.state.was_loading {
-                        timer::pause_game_time();
-                        self.state.was_loading = true;
+                    if self.state.switching_games {
+                        self.set_loading(true, now);
                     }
                 },
 
@@ -68,39 +399,47 @@ impl AutoSplitter {
 
                 _ => ()
             }
-        }
-
-        // Connected to any game
-        let Some(game_splitter) = game_splitter else { return; };
+            return;
+        };
 
-        match timer::state() {
+        match timer_state {
             TimerState::NotRunning => {
                 if Self::should_start(game_splitter) {
+                    self.reset_state();
                     if self.settings.start {
-                        timer::start();
-                        Self::initialize_game_time_workaround(); // FIXME remove when supported upstream
+                        self.commands.push(TimerCommand::Start);
+                        if game_splitter.needs_game_time_zero() {
+                            // FIXME Dirty hack results in game time being marginally shorter than real time (<1ms), remove when supported upstream
+                            self.commands.push(TimerCommand::SetGameTime(Duration::ZERO));
+                        }
+                        self.state.game_clock = Some(GameClock::started(now));
                     }
-                    self.reset_state();
                 }
             },
 
             TimerState::Running | TimerState::Paused => {
+                // started manually, game time is assumed to start from here
+                if self.state.game_clock.is_none() {
+                    self.state.game_clock = Some(GameClock::started(now));
+                }
+
                 // Reset logic
                 if Self::should_reset(game_splitter) {
                     if !self.state.autoreset_lockout && self.settings.reset {
-                        timer::reset();
-                        self.reset_state();
+                        self.reset_timer();
+                        game_splitter.soft_reset(); // the next run's change detection starts clean
                     }
                 }
                 // Splitting logic
+                // completion takes precedence, a split condition on the same tick is dropped
                 else if !self.state.switching_games {
                     if Self::game_completed(game_splitter) {
-                        timer::split();
+                        self.commands.push(TimerCommand::Split);
                         self.state.autoreset_lockout = true; // Disable autoresets in case stage splits are disabled
                         self.state.switching_games = true; // pause timer until game swap is completed
                     } else if Self::should_split(game_splitter) {
                         if self.settings.split {
-                            timer::split();
+                            self.split_timer();
                         }
                         self.state.autoreset_lockout = true; // Disable autoresets after the first split
                     }
@@ -108,17 +447,25 @@ impl AutoSplitter {
                 // Resume timer after game swap
                 if self.state.switching_games && Self::should_start(game_splitter) {
                     self.state.switching_games = false;
+                    self.state.swaps += 1;
+                    // game time is paused during the swap, so this is exactly the time of the earlier games
+                    self.state.game_time_base = self.state.game_clock.map_or(Duration::ZERO, |clock| clock.current(now));
+                    self.state.last_swap = Some(game_splitter.display_name());
                 }
                 // Load removal/timer pause for game swap
-                if self.is_loading(game_splitter) {
-                    if !self.state.was_loading {
-                        timer::pause_game_time();
-                        self.state.was_loading = true;
-                    }
-                } else {
-                    if self.state.was_loading {
-                        timer::resume_game_time();
-                        self.state.was_loading = false;
+                match game_splitter.load_strategy() {
+                    LoadStrategy::InGameTime => {
+                        // game time never advances on its own, it is set from the in-game timer
+                        self.set_loading(true, now);
+                        if !self.state.switching_games {
+                            if let Some(game_time) = game_splitter.game_time() {
+                                self.set_game_time(self.state.game_time_base + game_time, now);
+                            }
+                        }
+                    },
+                    strategy => {
+                        let loading = self.is_loading(game_splitter, strategy);
+                        self.set_loading(loading, now);
                     }
                 }
             },
@@ -130,12 +477,62 @@ impl AutoSplitter {
         }
     }
 
+    /// Sends this tick's commands and messages to the runtime
+    fn apply_commands(&mut self) {
+        for command in self.commands.drain(..) {
+            match command {
+                TimerCommand::Start => timer::start(),
+                TimerCommand::Split => timer::split(),
+                TimerCommand::Reset => timer::reset(),
+                TimerCommand::PauseGameTime => timer::pause_game_time(),
+                TimerCommand::ResumeGameTime => timer::resume_game_time(),
+                TimerCommand::SetGameTime(time) => timer::set_game_time(time),
+            }
+        }
+        for message in self.messages.drain(..) {
+            asr::print_message(&message);
+        }
+    }
+
+    /// Publishes the attached game
+    fn update_attached_game(&mut self, game_splitter: Option<&dyn GameAutoSplitter>) {
+        let game = game_splitter.map(|game_splitter| game_splitter.display_name());
+        if self.attached_game == game {
+            return;
+        }
+        self.attached_game = game;
+
+        if let Some(game) = game {
+            timer::set_variable("Game", game);
+        } else {
+            timer::set_variable("Game", "No game attached");
+        }
+    }
+
+    /// Logs every decision that starts applying this tick, without issuing any timer command
+    ///
+    /// Predicates are evaluated regardless of the timer state and of each other, so a tick can log several decisions
+    fn log_decisions(&mut self, game_splitter: &dyn GameAutoSplitter) {
+        let decisions = [
+            Self::should_reset(game_splitter),
+            Self::game_completed(game_splitter),
+            Self::should_split(game_splitter),
+            Self::should_start(game_splitter),
+        ];
+        for ((name, decision), previous) in DRY_RUN_DECISIONS.iter().zip(decisions).zip(self.dry_run_decisions) {
+            if decision && !previous {
+                self.messages.push(format!("[dry run] {}: would {}", game_splitter.display_name(), name));
+            }
+        }
+        self.dry_run_decisions = decisions;
+    }
+
     fn should_start(game_splitter: &dyn GameAutoSplitter) -> bool {
         return game_splitter.start();
     }
 
     fn should_reset(game_splitter: &dyn GameAutoSplitter) -> bool {
-        return game_splitter.reset();
+        return game_splitter.reset_allowed() && game_splitter.reset();
     }
 
     fn should_split(game_splitter: &dyn GameAutoSplitter) -> bool {
@@ -146,7 +543,359 @@ impl AutoSplitter {
         return game_splitter.completed();
     }
 
-    fn is_loading(&self, game_splitter: &dyn GameAutoSplitter) -> bool {
-        return self.state.switching_games || game_splitter.is_loading().unwrap_or(self.state.was_loading);
+    fn is_loading(&self, game_splitter: &dyn GameAutoSplitter, strategy: LoadStrategy) -> bool {
+        return self.state.switching_games || match strategy {
+            LoadStrategy::FadeBased | LoadStrategy::InGameTimeStall => game_splitter.is_loading().unwrap_or(self.state.was_loading),
+            LoadStrategy::None | LoadStrategy::InGameTime => false,
+        };
+    }
+
+    /// Pauses or resumes game time when the loading state changes
+    fn set_loading(&mut self, loading: bool, now: Duration) {
+        if loading {
+            if !self.state.was_loading {
+                self.commands.push(TimerCommand::PauseGameTime);
+                self.state.was_loading = true;
+                if let Some(clock) = &mut self.state.game_clock {
+                    clock.pause(now);
+                }
+            }
+        } else {
+            if self.state.was_loading {
+                self.commands.push(TimerCommand::ResumeGameTime);
+                self.state.was_loading = false;
+                if let Some(clock) = &mut self.state.game_clock {
+                    clock.resume(now);
+                }
+            }
+        }
+    }
+
+    fn set_game_time(&mut self, time: Duration, now: Duration) {
+        self.commands.push(TimerCommand::SetGameTime(time));
+        if let Some(clock) = &mut self.state.game_clock {
+            clock.set(time, now);
+        }
+    }
+}
+
+#[cfg(test)]
+impl AutoSplitterSettings {
+    /// Default settings, without registering them with the runtime
+    pub(crate) fn defaults() -> Self {
+        return AutoSplitterSettings {
+            _general_settings: Title,
+            start: true,
+            split: true,
+            reset: true,
+            swap_timeout: SwapTimeout::FiveMinutes,
+            _games_settings: Title,
+            enable_ror1: true,
+            enable_ror2: true,
+            enable_rorr: true,
+            attach_first: AttachFirst::Default,
+            _debug_settings: Title,
+            debug_messages: false,
+            dump_diagnostics: false,
+            dry_run: false,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use asr::Process;
+    use async_trait::async_trait;
+
+    use super::*;
+
+    /// Game with predicates set directly by the test
+    struct FakeGame {
+        start: bool,
+        reset: bool,
+        split: bool,
+        completed: bool,
+        loading: Option<bool>,
+        strategy: LoadStrategy,
+        game_time: Option<Duration>,
+        /// Number of `soft_reset()` calls
+        soft_resets: u32,
+    }
+
+    impl FakeGame {
+        fn new(strategy: LoadStrategy) -> Self {
+            return Self { start: false, reset: false, split: false, completed: false, loading: Some(false), strategy, game_time: None, soft_resets: 0 };
+        }
+    }
+
+    #[async_trait]
+    impl GameAutoSplitter for FakeGame {
+        fn display_name(&self) -> &'static str { return "Fake Game"; }
+        fn id(&self) -> GameId { return GameId::RiskOfRain2; }
+        fn process_names(&self) -> &[&str] { return &[]; }
+        fn register_settings(&mut self) {}
+        async fn attached(&mut self, _process: &Process, _autosplitter: &mut AutoSplitter) {}
+        fn start(&self) -> bool { return self.start; }
+        fn reset(&self) -> bool { return self.reset; }
+        fn split(&self) -> bool { return self.split; }
+        fn completed(&self) -> bool { return self.completed; }
+        fn is_loading(&self) -> Option<bool> { return self.loading; }
+        fn soft_reset(&mut self) { self.soft_resets += 1; }
+        fn dump_diagnostics(&self, _process: &Process) {}
+        fn progress(&self) -> Option<ArrayString<32>> { return None; }
+        fn load_strategy(&self) -> LoadStrategy { return self.strategy; }
+        fn game_time(&self) -> Option<Duration> { return self.game_time; }
+    }
+
+    fn seconds(seconds: i64) -> Duration { Duration::seconds(seconds) }
+
+    /// Runs one tick and returns the commands it decided
+    fn tick(autosplitter: &mut AutoSplitter, timer_state: TimerState, now: Duration, game: Option<&mut FakeGame>) -> Vec<TimerCommand> {
+        autosplitter.tick(timer_state, now, game.map(|game| game as &mut dyn GameAutoSplitter));
+        return autosplitter.commands.drain(..).collect();
+    }
+
+    /// Game time pauses while loading, is held while undetermined, and resumes once loading ends
+    fn check_pauses_while_loading(strategy: LoadStrategy) {
+        let mut autosplitter = AutoSplitter::with_settings(AutoSplitterSettings::defaults());
+        let mut game = FakeGame::new(strategy);
+
+        game.loading = Some(true);
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(0), Some(&mut game)), [TimerCommand::PauseGameTime]);
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(1), Some(&mut game)), []);
+        game.loading = None;
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(2), Some(&mut game)), []);
+        game.loading = Some(false);
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(3), Some(&mut game)), [TimerCommand::ResumeGameTime]);
+        game.loading = None;
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(4), Some(&mut game)), []);
+    }
+
+    #[test]
+    fn fade_based_pauses_while_loading() {
+        check_pauses_while_loading(LoadStrategy::FadeBased);
+    }
+
+    #[test]
+    fn in_game_time_stall_pauses_while_loading() {
+        check_pauses_while_loading(LoadStrategy::InGameTimeStall);
+    }
+
+    #[test]
+    fn no_load_removal_never_pauses() {
+        let mut autosplitter = AutoSplitter::with_settings(AutoSplitterSettings::defaults());
+        let mut game = FakeGame::new(LoadStrategy::None);
+        game.loading = Some(true);
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(0), Some(&mut game)), []);
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(1), Some(&mut game)), []);
+    }
+
+    #[test]
+    fn in_game_time_sets_game_time_and_holds_invalid_times() {
+        let mut autosplitter = AutoSplitter::with_settings(AutoSplitterSettings::defaults());
+        let mut game = FakeGame::new(LoadStrategy::InGameTime);
+        game.game_time = Some(seconds(5));
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(0), Some(&mut game)), [TimerCommand::PauseGameTime, TimerCommand::SetGameTime(seconds(5))]);
+        game.game_time = None;
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(1), Some(&mut game)), []);
+        game.game_time = Some(seconds(7));
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(2), Some(&mut game)), [TimerCommand::SetGameTime(seconds(7))]);
+    }
+
+    #[test]
+    fn game_time_pauses_during_game_swaps() {
+        let mut autosplitter = AutoSplitter::with_settings(AutoSplitterSettings::defaults());
+        let mut game = FakeGame::new(LoadStrategy::None);
+        game.completed = true;
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(0), Some(&mut game)), [TimerCommand::Split, TimerCommand::PauseGameTime]);
+        game.completed = false;
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(1), None), []);
+        game.start = true;
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(2), Some(&mut game)), [TimerCommand::ResumeGameTime]);
+        assert_eq!(autosplitter.state.last_swap, Some("Fake Game"));
+    }
+
+    #[test]
+    fn last_game_swap_is_cleared_on_disconnect_and_reset() {
+        let mut autosplitter = AutoSplitter::with_settings(AutoSplitterSettings::defaults());
+        let mut game = FakeGame::new(LoadStrategy::None);
+        let swap = |autosplitter: &mut AutoSplitter, game: &mut FakeGame| {
+            game.completed = true;
+            tick(autosplitter, TimerState::Running, seconds(0), Some(&mut *game));
+            game.completed = false;
+            game.start = true;
+            tick(autosplitter, TimerState::Running, seconds(1), Some(&mut *game));
+            game.start = false;
+            assert_eq!(autosplitter.state.last_swap, Some("Fake Game"));
+        };
+
+        swap(&mut autosplitter, &mut game);
+        tick(&mut autosplitter, TimerState::Running, seconds(2), None);
+        assert_eq!(autosplitter.state.last_swap, None);
+
+        // automatic resets are locked out after the completion split
+        swap(&mut autosplitter, &mut game);
+        autosplitter.force_reset_tick(Some(&mut game));
+        assert_eq!(autosplitter.state.last_swap, None);
+
+        swap(&mut autosplitter, &mut game);
+        tick(&mut autosplitter, TimerState::Ended, seconds(2), Some(&mut game));
+        assert_eq!(autosplitter.state.last_swap, None);
+    }
+
+    #[test]
+    fn game_swaps_time_out_without_a_next_game() {
+        let mut autosplitter = AutoSplitter::with_settings(AutoSplitterSettings { swap_timeout: SwapTimeout::OneMinute, ..AutoSplitterSettings::defaults() });
+        let mut game = FakeGame::new(LoadStrategy::None);
+        game.completed = true;
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(0), Some(&mut game)), [TimerCommand::Split, TimerCommand::PauseGameTime]);
+
+        let timeout = SwapTimeout::OneMinute.ticks().unwrap();
+        for _ in 1..timeout {
+            assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(1), None), []);
+            assert!(autosplitter.state.switching_games);
+        }
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(60), None), [TimerCommand::ResumeGameTime]);
+        assert!(!autosplitter.state.switching_games);
+    }
+
+    #[test]
+    fn game_swaps_never_time_out_when_disabled() {
+        let mut autosplitter = AutoSplitter::with_settings(AutoSplitterSettings { swap_timeout: SwapTimeout::Never, ..AutoSplitterSettings::defaults() });
+        let mut game = FakeGame::new(LoadStrategy::None);
+        game.completed = true;
+        tick(&mut autosplitter, TimerState::Running, seconds(0), Some(&mut game));
+        for _ in 0..SwapTimeout::FifteenMinutes.ticks().unwrap() {
+            assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(1), None), []);
+        }
+        assert!(autosplitter.state.switching_games);
+    }
+
+    #[test]
+    fn completion_replaces_a_split_on_the_same_tick() {
+        let mut autosplitter = AutoSplitter::with_settings(AutoSplitterSettings::defaults());
+        let mut game = FakeGame::new(LoadStrategy::None);
+        game.completed = true;
+        game.split = true;
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(0), Some(&mut game)), [TimerCommand::Split, TimerCommand::PauseGameTime]);
+        assert!(autosplitter.state.switching_games);
+    }
+
+    #[test]
+    fn forced_split_updates_state_like_an_automatic_one() {
+        let mut game = FakeGame::new(LoadStrategy::None);
+        game.split = true;
+        let mut automatic = AutoSplitter::with_settings(AutoSplitterSettings::defaults());
+        let automatic_commands = tick(&mut automatic, TimerState::Running, seconds(0), Some(&mut game));
+
+        let mut forced = AutoSplitter::with_settings(AutoSplitterSettings::defaults());
+        forced.force_split_tick(TimerState::Running);
+        assert_eq!(forced.commands.drain(..).collect::<Vec<_>>(), automatic_commands);
+        assert!(forced.state.autoreset_lockout && automatic.state.autoreset_lockout);
+
+        // neither resets automatically afterwards
+        game.split = false;
+        game.reset = true;
+        assert_eq!(tick(&mut forced, TimerState::Running, seconds(1), Some(&mut game)), []);
+        assert_eq!(tick(&mut automatic, TimerState::Running, seconds(1), Some(&mut game)), []);
+    }
+
+    #[test]
+    fn forced_split_needs_a_running_timer() {
+        let mut autosplitter = AutoSplitter::with_settings(AutoSplitterSettings::defaults());
+        autosplitter.force_split_tick(TimerState::NotRunning);
+        assert!(autosplitter.commands.is_empty());
+        assert!(!autosplitter.state.autoreset_lockout);
+    }
+
+    #[test]
+    fn forced_reset_soft_resets_the_game() {
+        let mut autosplitter = AutoSplitter::with_settings(AutoSplitterSettings::defaults());
+        let mut game = FakeGame::new(LoadStrategy::None);
+        autosplitter.state.autoreset_lockout = true;
+        autosplitter.force_reset_tick(Some(&mut game));
+        assert_eq!(autosplitter.commands.drain(..).collect::<Vec<_>>(), [TimerCommand::Reset]);
+        assert!(!autosplitter.state.autoreset_lockout);
+        assert_eq!(game.soft_resets, 1);
+    }
+
+    #[test]
+    fn in_game_time_adds_the_time_of_earlier_games() {
+        let mut autosplitter = AutoSplitter::with_settings(AutoSplitterSettings::defaults());
+
+        // first game in real time, started by the autosplitter and completed a minute later
+        let mut first = FakeGame::new(LoadStrategy::None);
+        first.start = true;
+        assert_eq!(tick(&mut autosplitter, TimerState::NotRunning, seconds(0), Some(&mut first)), [TimerCommand::Start, TimerCommand::SetGameTime(Duration::ZERO)]);
+        first.start = false;
+        tick(&mut autosplitter, TimerState::Running, seconds(30), Some(&mut first));
+        first.completed = true;
+        tick(&mut autosplitter, TimerState::Running, seconds(60), Some(&mut first));
+
+        // the swap itself takes a while, which is not game time
+        tick(&mut autosplitter, TimerState::Running, seconds(90), None);
+
+        // second game sets its own in-game time
+        let mut second = FakeGame::new(LoadStrategy::InGameTime);
+        second.start = true;
+        second.game_time = Some(Duration::ZERO);
+        let commands = tick(&mut autosplitter, TimerState::Running, seconds(120), Some(&mut second));
+        assert_eq!(commands.last(), Some(&TimerCommand::SetGameTime(seconds(60))));
+        second.start = false;
+        second.game_time = Some(seconds(10));
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(130), Some(&mut second)), [TimerCommand::SetGameTime(seconds(70))]);
+    }
+
+    #[test]
+    fn in_game_time_from_the_middle_of_a_game_is_relative_to_the_swap() {
+        let mut autosplitter = AutoSplitter::with_settings(AutoSplitterSettings::defaults());
+
+        let mut first = FakeGame::new(LoadStrategy::None);
+        first.start = true;
+        tick(&mut autosplitter, TimerState::NotRunning, seconds(0), Some(&mut first));
+        first.start = false;
+        first.completed = true;
+        tick(&mut autosplitter, TimerState::Running, seconds(60), Some(&mut first));
+
+        // second game resumes with fade based load removal, like RoR2 before its run timer exists
+        let mut second = FakeGame::new(LoadStrategy::FadeBased);
+        second.start = true;
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(120), Some(&mut second)).last(), Some(&TimerCommand::ResumeGameTime));
+        second.start = false;
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(125), Some(&mut second)), []);
+
+        // the run timer counts from the start of this game's run, on top of the time at the swap
+        second.strategy = LoadStrategy::InGameTime;
+        second.game_time = Some(seconds(3));
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(126), Some(&mut second)), [TimerCommand::PauseGameTime, TimerCommand::SetGameTime(seconds(63))]);
+    }
+
+    #[test]
+    fn dry_run_logs_but_makes_no_timer_calls() {
+        let mut autosplitter = AutoSplitter::with_settings(AutoSplitterSettings { dry_run: true, ..AutoSplitterSettings::defaults() });
+        let mut game = FakeGame::new(LoadStrategy::FadeBased);
+
+        game.start = true;
+        assert_eq!(tick(&mut autosplitter, TimerState::NotRunning, seconds(0), Some(&mut game)), []);
+        assert_eq!(autosplitter.messages.drain(..).collect::<Vec<_>>(), [String::from("[dry run] Fake Game: would start")]);
+
+        // decisions only print when they begin
+        game.loading = Some(true);
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(1), Some(&mut game)), []);
+        assert!(autosplitter.messages.is_empty());
+
+        game.start = false;
+        game.completed = true;
+        game.reset = true;
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(2), Some(&mut game)), []);
+        assert_eq!(autosplitter.messages.len(), 2);
+        autosplitter.messages.clear();
+
+        // neither a disconnect during a game swap nor the swap timing out pauses or resumes game time
+        autosplitter.state.switching_games = true;
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(3), None), []);
+        autosplitter.state.switching_ticks = SwapTimeout::FiveMinutes.ticks().unwrap();
+        assert_eq!(tick(&mut autosplitter, TimerState::Running, seconds(4), Some(&mut game)), []);
+        assert!(autosplitter.messages.is_empty());
     }
 }
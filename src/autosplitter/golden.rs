@@ -0,0 +1,74 @@
+use asr::{time::Duration, timer::TimerState};
+use std::{collections::HashMap, str::FromStr};
+
+use super::{AutoSplitter, AutoSplitterSettings, TimerCommand};
+use crate::game::GameAutoSplitter;
+
+/// Game reads of a golden run line, values persist until a later line changes them
+#[derive(Default)]
+pub struct Reads {
+    values: HashMap<String, Option<String>>,
+}
+
+impl Reads {
+    /// Value read for `key`, None if it was never set or is marked invalid with `-`
+    pub fn get<T: FromStr>(&self, key: &str) -> Option<T> {
+        let value = self.values.get(key)?.as_deref()?;
+        return Some(value.parse().unwrap_or_else(|_| panic!("{}={} does not parse", key, value)));
+    }
+}
+
+/// Game that can update its state from golden run reads instead of process memory
+pub trait Replay: GameAutoSplitter {
+    /// Updates the game state watchers from this tick's reads, in the order `attached` updates them
+    fn replay_tick(&mut self, reads: &Reads);
+}
+
+/// Replays a golden run and checks the timer events of every line
+///
+/// Each line holds `key=value` reads, optionally followed by `=>` and the expected events: start, split, complete, or reset.
+/// Lines without `=>` expect no events, `-` marks an invalid read, and `#` starts a comment.
+/// Every line is one tick, one second apart, with the timer state following the previous events.
+pub fn replay<G: Replay>(game: &mut G, golden: &str) {
+    let mut autosplitter = AutoSplitter::with_settings(AutoSplitterSettings::defaults());
+    let mut timer_state = TimerState::NotRunning;
+    let mut reads = Reads::default();
+
+    for (index, line) in golden.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (values, expected) = line.split_once("=>").unwrap_or((line, ""));
+        for value in values.split_whitespace() {
+            let Some((key, value)) = value.split_once('=') else {
+                panic!("line {}: \"{}\" is not a key=value read", index + 1, value);
+            };
+            reads.values.insert(key.to_owned(), (value != "-").then(|| value.to_owned()));
+        }
+
+        game.replay_tick(&reads);
+        let was_switching = autosplitter.state.switching_games;
+        autosplitter.tick(timer_state, Duration::seconds(index as i64), Some(game as &mut dyn GameAutoSplitter));
+        // a completion is the split that starts waiting for the next game
+        let completed = !was_switching && autosplitter.state.switching_games;
+
+        let mut events = Vec::new();
+        for command in autosplitter.commands.drain(..) {
+            match command {
+                TimerCommand::Start => {
+                    timer_state = TimerState::Running;
+                    events.push("start");
+                },
+                TimerCommand::Reset => {
+                    timer_state = TimerState::NotRunning;
+                    events.push("reset");
+                },
+                TimerCommand::Split if completed => { events.push("complete"); },
+                TimerCommand::Split => { events.push("split"); },
+                _ => (),
+            }
+        }
+        assert_eq!(events, expected.split_whitespace().collect::<Vec<_>>(), "line {}: {}", index + 1, line);
+    }
+}
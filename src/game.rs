@@ -1,16 +1,28 @@
-use { asr::Process, async_trait::async_trait };
+use { asr::{Address, PointerSize, Process, string::ArrayString, time::Duration, timer}, async_trait::async_trait, std::fmt::{Debug, Display, Write} };
+#[cfg(test)]
+use asr::settings::gui::Title;
 
 use crate::AutoSplitter;
 
+#[cfg(feature = "ror1")]
 pub mod risk_of_rain;
+#[cfg(feature = "ror2")]
 pub mod risk_of_rain_2;
+#[cfg(feature = "rorr")]
 pub mod risk_of_rain_returns;
+pub mod version_table;
 
 #[async_trait]
 pub trait GameAutoSplitter {
 
     // Autosplitter utility
 
+    /// Game name shown to the user
+    fn display_name(&self) -> &'static str;
+
+    /// Which supported game this is, for settings and abbreviations that must not depend on the display name
+    fn id(&self) -> GameId;
+
     /// Process name(s) the game can attach to
     fn process_names(&self) -> &[&str];
 
@@ -22,10 +34,12 @@ pub trait GameAutoSplitter {
     /// Helper that attaches to any of the process names
     fn attach_any(&self) -> Option<Process> {
         for process_name in self.process_names() {
-            let process = Process::attach(platform_process_name(process_name));
+            for platform_name in platform_process_names(process_name) {
+                let process = Process::attach(platform_name);
 
-            if process.is_some() {
-                return process;
+                if process.is_some() {
+                    return process;
+                }
             }
         }
         return None;
@@ -48,9 +62,15 @@ pub trait GameAutoSplitter {
     /// Returns true if game has reached the start condition
     fn reset(&self) -> bool;
 
+    /// Returns false in states where a reset would be wrong even if `reset()` is true
+    ///
+    /// Guards against transient reads, e.g. a menu room read for a tick during an end cutscene
+    fn reset_allowed(&self) -> bool { true }
+
     /// Returns true if game met a split condition
     ///
-    /// This explicitly ignores the game end condition
+    /// This explicitly ignores the game end condition.
+    /// Ignored on ticks where `completed()` is true, the completion split replaces it
     fn split(&self) -> bool;
 
     /// Returns true if game met the completion condition
@@ -62,16 +82,384 @@ pub trait GameAutoSplitter {
     ///
     /// None indicates undetermined loading state, which behaves by maintaining the previously known state
     fn is_loading(&self) -> Option<bool>;
+
+    /// Clears per-run game state after an automatic reset
+    ///
+    /// Watchers start over, so values from the previous run never show up as changes in the next run
+    fn soft_reset(&mut self);
+
+    /// Prints attach details and resolved addresses, for support when offsets break after a game update
+    fn dump_diagnostics(&self, process: &Process);
+
+    /// Short description of the run progress for the debug status line, e.g. "stage 3"
+    ///
+    /// None outside of a run
+    fn progress(&self) -> Option<ArrayString<32>>;
+
+
+    // Timing
+
+    /// How the autosplitter should remove loads for this game
+    fn load_strategy(&self) -> LoadStrategy;
+
+    /// Current in-game time, used by `LoadStrategy::InGameTime`
+    ///
+    /// Relative to the start of this game's run, the autosplitter adds the time of earlier games in a multigame run.
+    /// None indicates an invalid timer, which holds the previously set game time
+    fn game_time(&self) -> Option<Duration> { None }
+
+    /// Returns true if game time needs to be zeroed when the timer starts
+    ///
+    /// Games that set game time from their own in-game timer don't need the workaround
+    fn needs_game_time_zero(&self) -> bool { self.load_strategy() != LoadStrategy::InGameTime }
+}
+
+/// Load removal approach used by a game
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LoadStrategy {
+    /// Pause game time while `is_loading()` reports a screen fade
+    FadeBased,
+    /// Pause game time while `is_loading()` reports the in-game timer has stopped advancing
+    InGameTimeStall,
+    /// No load removal, game time follows real time
+    None,
+    /// Game time is set from `game_time()`, on top of the time of earlier games
+    InGameTime,
+}
+
+/// Maximum length of a process (comm) name on Linux
+const LINUX_COMM_LEN: usize = 15;
+
+/// Every game the autosplitter can be built with
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameId {
+    RiskOfRain,
+    RiskOfRain2,
+    RiskOfRainReturns,
+}
+
+impl GameId {
+    /// Abbreviation used in the status line
+    pub fn short_name(self) -> &'static str {
+        return match self {
+            GameId::RiskOfRain => "ror1",
+            GameId::RiskOfRain2 => "ror2",
+            GameId::RiskOfRainReturns => "rorr",
+        };
+    }
+}
+
+/// Supported game metadata for external configurators
+pub struct GameInfo {
+    pub id: GameId,
+    pub display_name: &'static str,
+    pub process_names: &'static [&'static str],
+    pub settings: &'static [SettingSchema],
+}
+
+/// Game setting metadata for external configurators
+pub struct SettingSchema {
+    /// Settings key, same as the `GameSettings` field name
+    pub key: &'static str,
+    pub description: &'static str,
+    pub default: SettingDefault,
+    /// Dropdown option keys in dropdown order, empty for checkboxes
+    pub options: &'static [&'static str],
 }
 
-/// Cross-platform process name
+#[derive(PartialEq, Debug)]
+pub enum SettingDefault {
+    /// Checkbox
+    Bool(bool),
+    /// Dropdown, holds the default option key
+    Choice(&'static str),
+}
+
+impl SettingSchema {
+    pub const fn bool(key: &'static str, description: &'static str, default: bool) -> Self {
+        Self { key, description, default: SettingDefault::Bool(default), options: &[] }
+    }
+
+    pub const fn choice(key: &'static str, description: &'static str, default: &'static str, options: &'static [&'static str]) -> Self {
+        Self { key, description, default: SettingDefault::Choice(default), options }
+    }
+}
+
+/// Dropdown setting enum
+pub trait Choice: Copy + PartialEq + Default + Debug + 'static {
+    /// Every option in dropdown order, the settings key of an option is its variant name
+    const OPTIONS: &'static [Self];
+}
+
+/// `GameSettings` field type, checked against its `SETTINGS_SCHEMA` entry by `assert_schema_matches!`
+#[cfg(test)]
+pub trait SchemaField {
+    /// Takes this field's entry from `schema` and checks it, titles have no entry
+    fn check_schema<'a>(&self, key: &str, schema: &mut impl Iterator<Item = &'a SettingSchema>);
+}
+
+#[cfg(test)]
+fn next_schema_entry<'a>(key: &str, schema: &mut impl Iterator<Item = &'a SettingSchema>) -> &'a SettingSchema {
+    let setting = schema.next().unwrap_or_else(|| panic!("{} is missing from the schema", key));
+    assert_eq!(setting.key, key);
+    return setting;
+}
+
+#[cfg(test)]
+impl SchemaField for Title {
+    fn check_schema<'a>(&self, _key: &str, _schema: &mut impl Iterator<Item = &'a SettingSchema>) {}
+}
+
+#[cfg(test)]
+impl SchemaField for bool {
+    fn check_schema<'a>(&self, key: &str, schema: &mut impl Iterator<Item = &'a SettingSchema>) {
+        let setting = next_schema_entry(key, schema);
+        assert!(matches!(setting.default, SettingDefault::Bool(_)), "{} is not a checkbox", key);
+        assert!(setting.options.is_empty(), "{} is a checkbox with options", key);
+    }
+}
+
+#[cfg(test)]
+impl<T: Choice> SchemaField for T {
+    fn check_schema<'a>(&self, key: &str, schema: &mut impl Iterator<Item = &'a SettingSchema>) {
+        let setting = next_schema_entry(key, schema);
+        let SettingDefault::Choice(default) = setting.default else { panic!("{} is not a dropdown", key) };
+        assert_eq!(default, format!("{:?}", T::default()), "{} default", key);
+        let options: Vec<_> = T::OPTIONS.iter().map(|option| format!("{:?}", option)).collect();
+        assert_eq!(setting.options, options, "{} options", key);
+    }
+}
+
+/// Checks a `SETTINGS_SCHEMA` against the fields of a `GameSettings` value
+///
+/// Every field has to be listed in declaration order, destructuring fails to compile when one is missing.
+/// Keys and order have to match, titles are skipped, and dropdowns have to list every option with the `#[default]` variant as the default
+#[cfg(test)]
+macro_rules! assert_schema_matches {
+    ($schema:expr, $settings:expr, { $($field:ident),* $(,)? }) => {{
+        let GameSettings { $($field),* } = $settings;
+        let mut schema = $schema.iter();
+        $( $crate::game::SchemaField::check_schema(&$field, stringify!($field), &mut schema); )*
+        assert!(schema.next().is_none(), "the schema has settings GameSettings doesn't");
+    }};
+}
+#[cfg(test)]
+pub(crate) use assert_schema_matches;
+
+/// Every game the autosplitter was built with, in default attach order
+pub fn supported_games() -> Vec<GameInfo> {
+    let mut games = Vec::new();
+    #[cfg(feature = "ror1")]
+    games.push(risk_of_rain::INFO);
+    #[cfg(feature = "ror2")]
+    games.push(risk_of_rain_2::INFO);
+    #[cfg(feature = "rorr")]
+    games.push(risk_of_rain_returns::INFO);
+    return games;
+}
+
+/// Cross-platform process name candidates, in the order they should be attached to
+///
+/// Depending on how the game is launched (native, Proton, Wine) Linux may expose either name
+fn platform_process_names(process_name: &str) -> impl Iterator<Item = &str> {
+    return process_name_candidates(process_name, asr::get_os().ok().unwrap().starts_with("linux"));
+}
+
+/// Process name candidates, longest first
 ///
 /// On Windows: full exe name
-/// On Linux: exe name truncated to 15 characters
-fn platform_process_name(process_name: &str) -> &str {
-    if asr::get_os().ok().unwrap().starts_with("linux") {
-        return &process_name[0..15];
-    } else {
-        return process_name;
+/// On Linux: full exe name, then every prefix of it down to the 15 character comm truncation
+///
+/// The runtime only attaches by exact name and can't list processes, so a prefix match against the full name
+/// is done by trying each prefix. Prefixes shorter than the truncation are never tried, they could name
+/// another game (e.g. "Risk of Rain" for "Risk of Rain 2.exe")
+fn process_name_candidates(process_name: &str, linux: bool) -> impl Iterator<Item = &str> {
+    let shortest = if linux { LINUX_COMM_LEN.min(process_name.len()) } else { process_name.len() };
+    return (shortest..=process_name.len()).rev().filter_map(move |len| process_name.get(..len));
+}
+
+/// Named pointer path: base address and offsets, None while unresolved
+pub type NamedPath = (&'static str, Option<(Address, Vec<u64>)>);
+
+/// Attach details kept for `GameAutoSplitter::dump_diagnostics`
+#[derive(Default)]
+pub struct Diagnostics {
+    pub process_name: Option<&'static str>,
+    /// Main module base address and size
+    pub module: Option<(Address, u64)>,
+    /// Detected game version
+    pub version: Option<&'static str>,
+    pub paths: Vec<NamedPath>,
+}
+
+impl Diagnostics {
+    /// Prints everything, resolving each path to the address of its value
+    ///
+    /// Unresolved paths and invalid pointers along a path are reported instead of failing
+    pub fn print(&self, display_name: &str, process: &Process, pointer_size: PointerSize) {
+        asr::print_message(&format!("{} diagnostics", display_name));
+        asr::print_message(&format!("  process: {}", self.process_name.unwrap_or("unknown")));
+        match self.module {
+            Some((base, size)) => asr::print_message(&format!("  module: 0x{:X} (0x{:X} bytes)", base.value(), size)),
+            None => asr::print_message("  module: unknown"),
+        }
+        asr::print_message(&format!("  version: {}", self.version.unwrap_or("unknown")));
+        for (name, path) in &self.paths {
+            match path {
+                Some((base, offsets)) => match resolve_path(process, *base, offsets, pointer_size) {
+                    Some(address) => asr::print_message(&format!("  {}: 0x{:X} {:X?} -> 0x{:X}", name, base.value(), offsets, address.value())),
+                    None => asr::print_message(&format!("  {}: 0x{:X} {:X?} -> invalid", name, base.value(), offsets)),
+                },
+                None => asr::print_message(&format!("  {}: unresolved", name)),
+            }
+        }
+    }
+}
+
+/// Address of the value at the end of a pointer path, None if any pointer along the path is invalid
+fn resolve_path(process: &Process, base: Address, offsets: &[u64], pointer_size: PointerSize) -> Option<Address> {
+    let Some((&last, derefs)) = offsets.split_last() else { return Some(base); };
+    if derefs.is_empty() {
+        return Some(base.add(last));
+    }
+    let pointer = match pointer_size {
+        PointerSize::Bit64 => process.read_pointer_path::<u64>(base, pointer_size, derefs).ok()?,
+        _ => process.read_pointer_path::<u32>(base, pointer_size, derefs).ok()?.into(),
     };
+    return Some(Address::new(pointer).add(last));
+}
+
+/// Formats a duration as HH:MM:SS for timer variables
+pub fn format_time(time: Duration) -> String {
+    let seconds = time.whole_seconds().max(0);
+    return format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60);
+}
+
+/// Formats run progress for `GameAutoSplitter::progress()`, e.g. "stage 3"
+pub fn format_progress(label: &str, value: i32) -> Option<ArrayString<32>> {
+    let mut progress = ArrayString::new();
+    write!(progress, "{} {}", label, value).ok()?;
+    return Some(progress);
+}
+
+/// Timer variable that is only published when its value changes
+///
+/// Cleared when dropped, so a game's variables disappear when its `attached` future ends with the game process
+pub struct TimerVariable<T> {
+    name: &'static str,
+    value: Option<T>,
+}
+
+impl<T: Copy + PartialEq + Display> TimerVariable<T> {
+    pub const fn new(name: &'static str) -> Self { Self { name, value: None } }
+
+    /// None clears the variable
+    pub fn update(&mut self, value: Option<T>) {
+        if self.value != value {
+            self.value = value;
+            timer::set_variable(self.name, &value.map(|value| value.to_string()).unwrap_or_default());
+        }
+    }
+}
+
+impl<T> Drop for TimerVariable<T> {
+    fn drop(&mut self) {
+        if self.value.is_some() {
+            timer::set_variable(self.name, "");
+        }
+    }
+}
+
+/// Watcher predicates as the games rely on them, and process name matching
+#[cfg(test)]
+mod tests {
+    use asr::watcher::Watcher;
+    use super::process_name_candidates;
+
+    fn watcher<T: Copy + Default>(values: &[Option<T>]) -> Watcher<T> {
+        let mut watcher = Watcher::default();
+        for &value in values {
+            watcher.update(value);
+        }
+        return watcher;
+    }
+
+    #[test]
+    fn becoming_valid_is_not_a_change() {
+        let pair = watcher(&[None, Some(3)]).pair.unwrap();
+        assert_eq!((pair.old, pair.current), (3, 3));
+        assert!(!pair.changed());
+        assert!(!pair.increased());
+    }
+
+    #[test]
+    fn an_invalid_read_clears_the_pair() {
+        assert!(watcher(&[Some(1), None]).pair.is_none());
+        // the next valid read starts over, a change across the invalid read is lost
+        assert!(!watcher(&[Some(1), None, Some(2)]).pair.unwrap().changed());
+    }
+
+    #[test]
+    fn changed_from_to_matches_both_values() {
+        let pair = watcher(&[Some(0), Some(1)]).pair.unwrap();
+        assert!(pair.changed());
+        assert!(pair.changed_from(&0));
+        assert!(pair.changed_to(&1));
+        assert!(pair.changed_from_to(&0, &1));
+        assert!(!pair.changed_from_to(&1, &0));
+        assert!(!pair.changed_from_to(&0, &2));
+
+        let pair = watcher(&[Some(1), Some(1)]).pair.unwrap();
+        assert!(!pair.changed_from_to(&1, &1));
+        assert!(!pair.changed_to(&1));
+    }
+
+    #[test]
+    fn increased_and_decreased_follow_the_last_two_reads() {
+        let pair = watcher(&[Some(1), Some(2)]).pair.unwrap();
+        assert!(pair.increased());
+        assert!(!pair.decreased());
+
+        let pair = watcher(&[Some(2.5), Some(0.0)]).pair.unwrap();
+        assert!(pair.decreased());
+        assert!(!pair.increased());
+
+        let pair = watcher(&[Some(1), Some(2), Some(2)]).pair.unwrap();
+        assert!(!pair.increased());
+        assert!(!pair.changed());
+    }
+
+    /// First candidate of `process_name` that one of the `running` process names matches exactly
+    fn attached<'a>(process_name: &'a str, linux: bool, running: &[&str]) -> Option<&'a str> {
+        return process_name_candidates(process_name, linux).find(|name| running.contains(name));
+    }
+
+    #[test]
+    fn linux_matches_truncated_and_full_names() {
+        assert_eq!(attached("Risk of Rain 2.exe", true, &["Risk of Rain 2.exe"]), Some("Risk of Rain 2.exe"));
+        assert_eq!(attached("Risk of Rain 2.exe", true, &["Risk of Rain 2."]), Some("Risk of Rain 2."));
+        // cut at a different length than the comm limit
+        assert_eq!(attached("Risk of Rain 2.exe", true, &["Risk of Rain 2.ex"]), Some("Risk of Rain 2.ex"));
+        // the full name wins when both are running
+        assert_eq!(attached("Risk of Rain 2.exe", true, &["Risk of Rain 2.", "Risk of Rain 2.exe"]), Some("Risk of Rain 2.exe"));
+        // shorter prefixes belong to other games
+        assert_eq!(attached("Risk of Rain 2.exe", true, &["Risk of Rain", "Risk of Rain.ex"]), None);
+        assert_eq!(attached("Risk of Rain.exe", true, &["Risk of Rain.ex"]), Some("Risk of Rain.ex"));
+        assert_eq!(attached("Risk of Rain.exe", true, &["Risk of Rain 2."]), None);
+    }
+
+    #[test]
+    fn short_names_and_windows_match_exactly() {
+        assert_eq!(process_name_candidates("ror.exe", true).collect::<Vec<_>>(), ["ror.exe"]);
+        assert_eq!(process_name_candidates("Risk of Rain 2.exe", false).collect::<Vec<_>>(), ["Risk of Rain 2.exe"]);
+        assert_eq!(attached("Risk of Rain 2.exe", false, &["Risk of Rain 2."]), None);
+    }
+
+    #[test]
+    fn nan_never_increases() {
+        let pair = watcher(&[Some(1.0), Some(f64::NAN)]).pair.unwrap();
+        assert!(!pair.increased());
+        let pair = watcher(&[Some(f64::NAN), Some(1.0)]).pair.unwrap();
+        assert!(!pair.increased());
+    }
 }
@@ -1,19 +1,207 @@
-use asr::{deep_pointer::DeepPointer, future::{next_tick, retry}, Process, settings::{Gui, gui::Title}, watcher::Watcher};
+use asr::{future::{next_tick, retry}, PointerSize, Process, settings::{Gui, gui::Title}, string::ArrayString, time::Duration, watcher::Watcher};
 use async_trait::async_trait;
 use derive;
+use std::fmt::Write;
 
-use crate::game;
+use crate::game::{self, GameAutoSplitter};
 use crate::AutoSplitter;
 
+use version_details::*;
+
 const TARGET_PROCESS_NAMES : [&str; 2] = ["ROR_GMS_controller.exe", "Risk of Rain.exe"];
 
+pub const INFO: game::GameInfo = game::GameInfo {
+    id: game::GameId::RiskOfRain,
+    display_name: "Risk of Rain",
+    process_names: &TARGET_PROCESS_NAMES,
+    settings: SETTINGS_SCHEMA,
+};
+
 #[derive(Gui)]
 pub struct GameSettings {
     /// Risk of Rain Settings
     pub _ror1_settings: Title,
     /// Split on stage transitions
+    ///
+    /// Only splits when the stage changes, not on every room, see the per-stage toggles below
     #[default = false]
     pub ror1_stages: bool,
+    /// Split when leaving Stage 1
+    #[default = true]
+    pub ror1_split_stage_1: bool,
+    /// Split when leaving Stage 2
+    #[default = true]
+    pub ror1_split_stage_2: bool,
+    /// Split when leaving Stage 3
+    #[default = true]
+    pub ror1_split_stage_3: bool,
+    /// Split when leaving Stage 4
+    #[default = true]
+    pub ror1_split_stage_4: bool,
+    /// Split when leaving Stage 5
+    #[default = true]
+    pub ror1_split_stage_5: bool,
+    /// Split when entering and leaving the hidden stage (rPigbeach)
+    ///
+    /// Independent of stage transition splits, the hidden stage is never a stage transition on its own
+    #[default = false]
+    pub ror1_pigbeach: bool,
+    /// Reset on the main menu (rStart)
+    #[default = true]
+    pub ror1_reset_main_menu: bool,
+    /// Reset on the online co-op lobby (rSelectMult)
+    #[default = true]
+    pub ror1_reset_online_lobby: bool,
+    /// Reset on the single player and local co-op lobbies (rSelect, rSelectCoop)
+    ///
+    /// Quitting a run from the pause menu returns to these lobbies
+    #[default = false]
+    pub ror1_reset_local_lobby: bool,
+    /// Reset when Time Alive restarts from zero
+    ///
+    /// Catches a run restart that never visits a menu or lobby. Ignored for game versions where Time Alive has not been located
+    #[default = false]
+    pub ror1_reset_on_restart: bool,
+    /// Timing method
+    pub ror1_timing: Timing,
+    /// Only start with this survivor
+    ///
+    /// Ignored for game versions where the survivor has not been located
+    pub ror1_required_survivor: RequiredSurvivor,
+    /// Only start on this difficulty
+    ///
+    /// Ignored for game versions where the difficulty has not been located
+    pub ror1_required_difficulty: RequiredDifficulty,
+}
+
+/// Settings metadata for external configurators, keep in sync with `GameSettings`
+pub const SETTINGS_SCHEMA: &[game::SettingSchema] = &[
+    game::SettingSchema::bool("ror1_stages", "Split on stage transitions", false),
+    game::SettingSchema::bool("ror1_split_stage_1", "Split when leaving Stage 1", true),
+    game::SettingSchema::bool("ror1_split_stage_2", "Split when leaving Stage 2", true),
+    game::SettingSchema::bool("ror1_split_stage_3", "Split when leaving Stage 3", true),
+    game::SettingSchema::bool("ror1_split_stage_4", "Split when leaving Stage 4", true),
+    game::SettingSchema::bool("ror1_split_stage_5", "Split when leaving Stage 5", true),
+    game::SettingSchema::bool("ror1_pigbeach", "Split when entering and leaving the hidden stage (rPigbeach)", false),
+    game::SettingSchema::bool("ror1_reset_main_menu", "Reset on the main menu (rStart)", true),
+    game::SettingSchema::bool("ror1_reset_online_lobby", "Reset on the online co-op lobby (rSelectMult)", true),
+    game::SettingSchema::bool("ror1_reset_local_lobby", "Reset on the single player and local co-op lobbies (rSelect, rSelectCoop)", false),
+    game::SettingSchema::bool("ror1_reset_on_restart", "Reset when Time Alive restarts from zero", false),
+    game::SettingSchema::choice("ror1_timing", "Timing method", "RealTime", &["RealTime", "InGameTime"]),
+    game::SettingSchema::choice("ror1_required_survivor", "Only start with this survivor", "Any", &["Any", "Commando", "Enforcer", "Bandit", "Huntress", "HanD", "Engineer", "Miner", "Sniper", "Acrid", "Mercenary", "Loader", "Chef"]),
+    game::SettingSchema::choice("ror1_required_difficulty", "Only start on this difficulty", "Any", &["Any", "Drizzle", "Rainstorm", "Monsoon"]),
+];
+
+#[derive(Gui, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum Timing {
+    /// Real Time (game time starts marginally behind real time, <1ms)
+    #[default]
+    RealTime,
+    /// In-Game Time (Time Alive)
+    InGameTime,
+}
+
+impl game::Choice for Timing {
+    const OPTIONS: &'static [Self] = &[Self::RealTime, Self::InGameTime];
+}
+
+#[derive(Gui, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum RequiredSurvivor {
+    /// Any
+    #[default]
+    Any,
+    /// Commando
+    Commando,
+    /// Enforcer
+    Enforcer,
+    /// Bandit
+    Bandit,
+    /// Huntress
+    Huntress,
+    /// HAN-D
+    HanD,
+    /// Engineer
+    Engineer,
+    /// Miner
+    Miner,
+    /// Sniper
+    Sniper,
+    /// Acrid
+    Acrid,
+    /// Mercenary
+    Mercenary,
+    /// Loader
+    Loader,
+    /// CHEF
+    Chef,
+}
+
+impl game::Choice for RequiredSurvivor {
+    const OPTIONS: &'static [Self] = &[
+        Self::Any,
+        Self::Commando,
+        Self::Enforcer,
+        Self::Bandit,
+        Self::Huntress,
+        Self::HanD,
+        Self::Engineer,
+        Self::Miner,
+        Self::Sniper,
+        Self::Acrid,
+        Self::Mercenary,
+        Self::Loader,
+        Self::Chef,
+    ];
+}
+
+impl RequiredSurvivor {
+    /// In-game survivor index (lobby selection order), None for any survivor
+    pub fn index(self) -> Option<i32> {
+        return match self {
+            RequiredSurvivor::Any => None,
+            RequiredSurvivor::Commando => Some(0),
+            RequiredSurvivor::Enforcer => Some(1),
+            RequiredSurvivor::Bandit => Some(2),
+            RequiredSurvivor::Huntress => Some(3),
+            RequiredSurvivor::HanD => Some(4),
+            RequiredSurvivor::Engineer => Some(5),
+            RequiredSurvivor::Miner => Some(6),
+            RequiredSurvivor::Sniper => Some(7),
+            RequiredSurvivor::Acrid => Some(8),
+            RequiredSurvivor::Mercenary => Some(9),
+            RequiredSurvivor::Loader => Some(10),
+            RequiredSurvivor::Chef => Some(11),
+        };
+    }
+}
+
+#[derive(Gui, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum RequiredDifficulty {
+    /// Any
+    #[default]
+    Any,
+    /// Drizzle
+    Drizzle,
+    /// Rainstorm
+    Rainstorm,
+    /// Monsoon
+    Monsoon,
+}
+
+impl game::Choice for RequiredDifficulty {
+    const OPTIONS: &'static [Self] = &[Self::Any, Self::Drizzle, Self::Rainstorm, Self::Monsoon];
+}
+
+impl RequiredDifficulty {
+    /// In-game difficulty level, None for any difficulty
+    pub fn level(self) -> Option<i32> {
+        return match self {
+            RequiredDifficulty::Any => None,
+            RequiredDifficulty::Drizzle => Some(1),
+            RequiredDifficulty::Rainstorm => Some(2),
+            RequiredDifficulty::Monsoon => Some(3),
+        };
+    }
 }
 
 /// Game state watchers
@@ -21,26 +209,104 @@ pub struct GameSettings {
 pub struct GameVars {
     /// GameMaker room ID
     pub room: Watcher<i32>,
+    /// Stage of the current room, see `stage_of`
+    ///
+    /// Invalid outside of a run, the hidden stage keeps the stage it was entered from
+    pub stage: Watcher<i32>,
     /// Control Panel activated after the Providence fight
     ///
     /// This variable is only active on the final stage
     pub run_end_flag: Watcher<i32>,
     /// Time Alive
     pub in_game_time: Watcher<f64>,
+    /// Survivor index of the local player selected in the lobby
+    ///
+    /// Invalid in menus, cutscenes, and lobbies, becomes valid on the first tick of a run
+    pub survivor: Watcher<i32>,
+    /// Difficulty level selected in the lobby (1 = Drizzle, 2 = Rainstorm, 3 = Monsoon)
+    ///
+    /// Invalid in menus, cutscenes, and lobbies, becomes valid on the first tick of a run
+    pub difficulty: Watcher<i32>,
+    /// Last valid Time Alive, since it can be briefly invalid at the end of a run
+    pub last_in_game_time: Option<f64>,
+    /// Time Alive the run was completed at, kept for run verification until the next reset
+    pub final_in_game_time: Option<f64>,
 }
 
-/// Only supports v1.2.2
 pub struct Game {
     pub settings: GameSettings,
     pub game_state: GameVars,
+    diagnostics: game::Diagnostics,
 }
 
 impl Game {
-    pub fn new() -> Self { Self { settings: GameSettings::register(), game_state: GameVars::default() } }
+    pub fn new() -> Self { Self { settings: GameSettings::register(), game_state: GameVars::default(), diagnostics: game::Diagnostics::default() } }
 
     fn reset_state(&mut self) {
         self.game_state = GameVars::default();
     }
+
+    /// Per-stage toggle for leaving `stage`
+    fn stage_split_enabled(&self, stage: i32) -> bool {
+        return match stage {
+            1 => self.settings.ror1_split_stage_1,
+            2 => self.settings.ror1_split_stage_2,
+            3 => self.settings.ror1_split_stage_3,
+            4 => self.settings.ror1_split_stage_4,
+            5 => self.settings.ror1_split_stage_5,
+            _ => true,
+        };
+    }
+
+    /// Stage of the current room, the hidden stage keeps the stage it was entered from
+    fn update_stage(&mut self) {
+        let stage = match self.game_state.room.pair.map(|room| room.current) {
+            Some(HIDDEN_ROOM) => self.game_state.stage.pair.map(|stage| stage.current),
+            room => room.and_then(stage_of),
+        };
+        self.game_state.stage.update(stage);
+    }
+
+    /// Outside of menus, cutscenes, and lobbies
+    fn in_run(&self) -> bool {
+        return self.game_state.room.pair.is_some_and(|room| !MENU_ROOMS.contains(&room.current) && !LOBBY_ROOMS.contains(&room.current));
+    }
+
+    /// Only reads the survivor during a run, so it resolves on the tick a lobby is left for a stage
+    fn update_survivor(&mut self, survivor: Option<i32>) {
+        let survivor = survivor.filter(|_| self.in_run());
+        self.game_state.survivor.update(survivor);
+    }
+
+    /// Only reads the difficulty during a run, so it resolves on the tick a lobby is left for a stage
+    fn update_difficulty(&mut self, difficulty: Option<i32>) {
+        let difficulty = difficulty.filter(|_| self.in_run());
+        self.game_state.difficulty.update(difficulty);
+    }
+
+    /// Keeps the last finite Time Alive, it can be briefly invalid at the end of a run
+    fn update_last_in_game_time(&mut self) {
+        if let Some(in_game_time) = self.game_state.in_game_time.pair {
+            if in_game_time.current.is_finite() {
+                self.game_state.last_in_game_time = Some(in_game_time.current);
+            }
+        }
+    }
+
+    /// Snapshots the Time Alive on completion
+    fn update_final_in_game_time(&mut self) {
+        if self.completed() {
+            self.game_state.final_in_game_time = self.game_state.last_in_game_time;
+        }
+    }
+
+    /// Value of the "Final IGT" timer variable, None until the run is completed
+    fn final_igt(&self) -> Option<ArrayString<16>> {
+        let final_in_game_time = self.game_state.final_in_game_time?;
+        let mut final_igt = ArrayString::new();
+        write!(final_igt, "{}", game::format_time(Duration::seconds_f64(final_in_game_time))).ok()?;
+        return Some(final_igt);
+    }
 }
 
 
@@ -58,6 +324,9 @@ impl Game {
 // 7 => Local co-op lobby
 // 40 => Online co-op lobby
 //
+// Artifacts are selected from within the lobby rooms, there is no separate artifact setup room,
+// so selecting artifacts before a run never changes the room ID (no start, split, or extra reset).
+//
 // 18-38 => Stages & variants
 // 41 => Contact Light
 //
@@ -67,11 +336,87 @@ impl Game {
 
 const MENU_ROOMS : [i32; 16] = [0, 1, 2, 3, 4, 5, 9, 10, 11, 12, 13, 14, 15, 16, 17, 39];
 const LOBBY_ROOMS : [i32; 3] = [6, 7, 40];
+const OUTRO_ROOMS : [i32; 3] = [15, 16, 17];
+/// UES Contact Light
+const FINAL_ROOM : i32 = 41;
+/// Hidden stage
+const HIDDEN_ROOM : i32 = Room::rPigbeach as i32;
+
+/// First and last room ID of each stage, in stage order
+///
+/// Each stage has two environments with a few variants each, only one of them is visited per loop.
+/// rPigbeach falls within Stage 3 and has to be checked before using this
+const STAGE_ROOMS : [(i32, i32); 6] = [
+    (Room::r1_1_1 as i32, Room::r1_2_3 as i32),
+    (Room::r2_1_1 as i32, Room::r2_2_2 as i32),
+    (Room::r3_1_1 as i32, Room::r3_2_2 as i32),
+    (Room::r4_1_1 as i32, Room::r4_2_2 as i32),
+    (Room::r5_1_1 as i32, Room::r5_1_2 as i32),
+    (Room::r6_1_1 as i32, Room::r6_1_1 as i32),
+];
+
+/// Stage number (1-6) of a room, None for menus, lobbies, and cutscenes
+fn stage_of(room: i32) -> Option<i32> {
+    return STAGE_ROOMS.iter()
+        .position(|&(first, last)| (first..=last).contains(&room))
+        .map(|index| index as i32 + 1);
+}
+
+/// Start condition: the room ID went from a lobby to a non-menu/cutscene/lobby room
+///
+/// Leaving a lobby for a cutscene, a menu, or another lobby (e.g. backing out to rHost) never starts
+fn evaluate_start(old: i32, new: i32) -> bool {
+    return old != new
+        && LOBBY_ROOMS.contains(&old)
+        && !LOBBY_ROOMS.contains(&new)
+        && !MENU_ROOMS.contains(&new);
+}
+
+/// Display name of an in-game difficulty level
+fn difficulty_name(level: i32) -> &'static str {
+    return match level {
+        1 => "Drizzle",
+        2 => "Rainstorm",
+        3 => "Monsoon",
+        _ => "Unknown",
+    };
+}
+
+/// Display name of an in-game survivor index
+fn survivor_name(index: i32) -> &'static str {
+    return match index {
+        0 => "Commando",
+        1 => "Enforcer",
+        2 => "Bandit",
+        3 => "Huntress",
+        4 => "HAN-D",
+        5 => "Engineer",
+        6 => "Miner",
+        7 => "Sniper",
+        8 => "Acrid",
+        9 => "Mercenary",
+        10 => "Loader",
+        11 => "CHEF",
+        _ => "Unknown",
+    };
+}
+
+/// A value that could not be read, or no requirement (None), always passes
+fn meets_requirement(value: Option<i32>, required: Option<i32>) -> bool {
+    return match (value, required) {
+        (Some(value), Some(required)) => value == required,
+        _ => true,
+    };
+}
 
 #[async_trait]
 impl game::GameAutoSplitter for Game {
+    fn display_name(&self) -> &'static str { return INFO.display_name; }
+
+    fn id(&self) -> game::GameId { return INFO.id; }
+
     /// "ROR_GMS_controller.exe" or "Risk of Rain.exe"
-    fn process_names(&self) -> &[&str] { return &TARGET_PROCESS_NAMES; }
+    fn process_names(&self) -> &[&str] { return INFO.process_names; }
 
     fn register_settings(&mut self) {
         self.settings = GameSettings::register();
@@ -81,13 +426,39 @@ impl game::GameAutoSplitter for Game {
         self.reset_state();
 
         // ugly way to get the main module address; LSO provides no way to get the currently attached process name
-        let (main_module, _main_module_size) = retry(|| {
-            TARGET_PROCESS_NAMES.iter().find_map(|&m| process.get_module_range(m).ok() )
+        let (main_module_name, (main_module, main_module_size)) = retry(|| {
+            TARGET_PROCESS_NAMES.iter().find_map(|&m| process.get_module_range(m).ok().map(|range| (m, range)) )
         }).await;
 
-        let room = DeepPointer::<1>::new_32bit(main_module, &[0x2BED7A8]);
-        let run_end_flag = DeepPointer::<5>::new_32bit(main_module, &[0x2BEB5E0, 0x0, 0x548, 0xC, 0xB4]);
-        let in_game_time = DeepPointer::<10>::new_32bit(main_module, &[0x02BEB5E0, 0x0, 0x28, 0xC, 0xBC, 0x8, 0x0, 0x720, 0x8, 0x1EC0]);
+        // game version detection and handling
+        let mut reported = false;
+        let mut room_check = RoomCheck::default();
+        let (room, run_end_flag, in_game_time, survivor, difficulty) = loop {
+            if let Some(pointers) = find_gamevar_pointers(process, &main_module, main_module_size, &mut room_check) {
+                break pointers;
+            }
+            // report once so users can submit the details of their version, a known room that isn't stable yet is no reason to
+            if !reported && room_check.unknown() && autosplitter.debug() {
+                report_unknown_version(process, &main_module, main_module_size);
+                reported = true;
+            }
+            // unsupported versions keep polling until the game closes, the timer is handled as if no game was attached
+            autosplitter.update_loop(None);
+            next_tick().await;
+        };
+
+        let (version, paths) = version_diagnostics(process, &main_module, main_module_size).unzip();
+        self.diagnostics = game::Diagnostics {
+            process_name: Some(main_module_name),
+            module: Some((main_module, main_module_size)),
+            version,
+            paths: paths.unwrap_or_default(),
+        };
+
+        let mut room_var = game::TimerVariable::new("Room");
+        let mut survivor_var = game::TimerVariable::new("Survivor");
+        let mut difficulty_var = game::TimerVariable::new("Difficulty");
+        let mut final_igt_var = game::TimerVariable::new("Final IGT");
 
         loop {
             // update game state watchers
@@ -104,11 +475,28 @@ impl game::GameAutoSplitter for Game {
                 }
             );
             self.game_state.in_game_time.update(
-                match in_game_time.deref::<f64>(&process) {
-                    Ok(val) => Some(val),
-                    _ => None
-                }
+                in_game_time.as_ref().and_then(|in_game_time| in_game_time.deref::<f64>(&process).ok())
+            );
+            self.update_stage();
+            self.update_survivor(
+                survivor.as_ref().and_then(|survivor| survivor.deref::<i32>(&process).ok())
             );
+            self.update_difficulty(
+                difficulty.as_ref().and_then(|difficulty| difficulty.deref::<i32>(&process).ok())
+            );
+
+            self.update_last_in_game_time();
+            self.update_final_in_game_time();
+
+            final_igt_var.update(self.final_igt());
+
+            room_var.update(self.game_state.room.pair.filter(|_| autosplitter.debug()).map(|room| room.current));
+            survivor_var.update(self.game_state.survivor.pair.filter(|_| autosplitter.debug()).map(|survivor| survivor_name(survivor.current)));
+            difficulty_var.update(self.game_state.difficulty.pair.filter(|_| autosplitter.debug()).map(|difficulty| difficulty_name(difficulty.current)));
+
+            if autosplitter.diagnostics_requested() {
+                self.dump_diagnostics(process);
+            }
 
             self.settings.update();
             // cede control to main autosplitter logic loop
@@ -117,34 +505,65 @@ impl game::GameAutoSplitter for Game {
         }
     }
 
-    /// Start when entering a game from a lobby
-    ///
-    /// Simply checks that the room ID went from a lobby to a non-menu/cutscene/lobby room
+    /// Start when entering a game from a lobby with the required survivor and difficulty, see `evaluate_start`
     fn start(&self) -> bool {
         if let Some(room) = self.game_state.room.pair {
-            return room.changed() && LOBBY_ROOMS.contains(&room.old) && !MENU_ROOMS.contains(&room.current);
+            // the survivor and difficulty only become valid on this tick, so they are read from the run itself
+            let survivor = self.game_state.survivor.pair.map(|survivor| survivor.current);
+            let difficulty = self.game_state.difficulty.pair.map(|difficulty| difficulty.current);
+            return evaluate_start(room.old, room.current)
+                && meets_requirement(survivor, self.settings.ror1_required_survivor.index())
+                && meets_requirement(difficulty, self.settings.ror1_required_difficulty.level());
         }
         return false;
     }
 
-    /// Reset when entering the main menu or lobby
+    /// Reset on the selected menu and lobby rooms, or optionally on a run restart
     ///
-    /// Specifically detect room IDs 2 (rStart) and 40 (rSelectMult)
+    /// Room IDs 2 (rStart), 40 (rSelectMult), and 6/7 (rSelect/rSelectCoop).
+    /// Like every reset, these only apply before the first split of the run
     fn reset(&self) -> bool {
+        if self.settings.ror1_reset_on_restart {
+            if let Some(in_game_time) = self.game_state.in_game_time.pair {
+                if in_game_time.old > 0.0 && in_game_time.current == 0.0 {
+                    return true;
+                }
+            }
+        }
         if let Some(room) = self.game_state.room.pair {
-            return room.current == 2 || room.current == 40;
+            return match room.current {
+                2 => self.settings.ror1_reset_main_menu,
+                40 => self.settings.ror1_reset_online_lobby,
+                6 | 7 => self.settings.ror1_reset_local_lobby,
+                _ => false,
+            };
         }
         return false;
     }
 
+    /// No resets while on or just leaving the outro cutscene and credits
+    fn reset_allowed(&self) -> bool {
+        if let Some(room) = self.game_state.room.pair {
+            return !(OUTRO_ROOMS.contains(&room.old) || OUTRO_ROOMS.contains(&room.current));
+        }
+        return true;
+    }
+
     /// Split on stage change
+    ///
+    /// Rooms are grouped into stages, so variants of the same stage never split and the hidden stage has its own toggle
     fn split(&self) -> bool {
-
-        // Stage/room changed
+        // Hidden stage entered or left, a stage change on the same tick is still a single split
         if let Some(room) = self.game_state.room.pair {
-            if room.changed() {
-                // Don't split when returning to/from the lobby or after rebooting the game
-                return self.settings.ror1_stages && !(MENU_ROOMS.contains(&room.old) || MENU_ROOMS.contains(&room.current) || LOBBY_ROOMS.contains(&room.old) || LOBBY_ROOMS.contains(&room.current));
+            if self.settings.ror1_pigbeach && room.changed() && (room.old == HIDDEN_ROOM || room.current == HIDDEN_ROOM) {
+                return true;
+            }
+        }
+
+        // Stage changed, the stage is invalid in menus and lobbies so returning to them never splits
+        if let Some(stage) = self.game_state.stage.pair {
+            if stage.changed() {
+                return self.settings.ror1_stages && self.stage_split_enabled(stage.old);
             }
         }
         return false;
@@ -155,7 +574,7 @@ impl game::GameAutoSplitter for Game {
     /// Detects activating the console in room ID 41 (r6_1_1)
     fn completed(&self) -> bool {
         if let (Some(room), Some(run_end_flag)) = (self.game_state.room.pair, self.game_state.run_end_flag.pair) {
-            return room.current == 41 && run_end_flag.changed_from_to(&0, &1);
+            return room.current == FINAL_ROOM && run_end_flag.changed_from_to(&0, &1);
         }
         return false;
     }
@@ -163,9 +582,39 @@ impl game::GameAutoSplitter for Game {
     /// No load removal (always false)
     fn is_loading(&self) -> Option<bool> { Some(false) }
 
+    fn soft_reset(&mut self) {
+        self.reset_state();
+    }
+
+    fn dump_diagnostics(&self, process: &Process) {
+        self.diagnostics.print(self.display_name(), process, PointerSize::Bit32);
+    }
+
+    /// Current room, outside of menus and lobbies
+    fn progress(&self) -> Option<ArrayString<32>> {
+        let room = self.game_state.room.pair?.current;
+        if MENU_ROOMS.contains(&room) || LOBBY_ROOMS.contains(&room) {
+            return None;
+        }
+        return game::format_progress("room", room);
+    }
+
+    /// No load removal, or game time synced to Time Alive
+    fn load_strategy(&self) -> game::LoadStrategy {
+        return match self.settings.ror1_timing {
+            Timing::RealTime => game::LoadStrategy::None,
+            Timing::InGameTime => game::LoadStrategy::InGameTime,
+        };
+    }
+
+    /// Last valid Time Alive, holds the previous time while invalid
+    fn game_time(&self) -> Option<Duration> {
+        return self.game_state.last_in_game_time.map(Duration::seconds_f64);
+    }
+
 }
 
-/// Purely for documentation's sake
+/// GameMaker room IDs, used to group rooms into stages
 #[allow(non_camel_case_types)]
 pub enum Room {
     /// White Screen
@@ -180,9 +629,9 @@ pub enum Room {
     rBook,
     /// Scores and Unlockables
     rHighscore,
-    /// Single Player Lobby
+    /// Single Player Lobby, including artifact selection
     rSelect,
-    /// Local Co-Op Lobby
+    /// Local Co-Op Lobby, including artifact selection
     rSelectCoop,
     /// Unused?
     rIntro,
@@ -216,8 +665,429 @@ pub enum Room {
     r5_1_1, r5_1_2,
     /// Online Co-Op Host/Join screen
     rHost,
-    /// Online Co-Op Lobby
+    /// Online Co-Op Lobby, including artifact selection
     rSelectMult,
     /// UES Contact Light
     r6_1_1,
 }
+
+mod version_details {
+    use asr::{Address, deep_pointer::DeepPointer, Process};
+
+    use crate::game::{NamedPath, version_table::{GameVersionData, VersionTable}};
+
+    use super::Room;
+
+// public interface
+
+    /// Guaranteed to be large enough to hold a DeepPointer to "room" from any version
+    pub type RoomPointer = DeepPointer::<{VERSIONS.path_len(GameVar::Room as usize)}>;
+    /// Guaranteed to be large enough to hold a DeepPointer to "run_end_flag" from any version
+    pub type RunEndFlagPointer = DeepPointer::<{VERSIONS.path_len(GameVar::RunEndFlag as usize)}>;
+    /// Guaranteed to be large enough to hold a DeepPointer to "in_game_time" from any version
+    pub type IGTPointer = DeepPointer::<{VERSIONS.path_len(GameVar::InGameTime as usize)}>;
+    /// Guaranteed to be large enough to hold a DeepPointer to "survivor" from any version
+    pub type SurvivorPointer = DeepPointer::<{VERSIONS.path_len(GameVar::Survivor as usize)}>;
+    /// Guaranteed to be large enough to hold a DeepPointer to "difficulty" from any version
+    pub type DifficultyPointer = DeepPointer::<{VERSIONS.path_len(GameVar::Difficulty as usize)}>;
+
+    /// Autodetects game version and locates offsets for game vars
+    ///
+    /// "in_game_time", "survivor", and "difficulty" are None for versions where they have not been located.
+    /// None until `room_check` trusts the room pointer, which never happens for unsupported versions
+    pub fn find_gamevar_pointers<'a>(process: &'a Process, module_offset: &'a Address, module_size: u64, room_check: &mut RoomCheck) -> Option<(RoomPointer, RunEndFlagPointer, Option<IGTPointer>, Option<SurvivorPointer>, Option<DifficultyPointer>)> {
+        let gv = VERSIONS.detect(process, module_offset, module_size)?;
+        let room = RoomPointer::new_32bit(*module_offset, gv.offsets[GameVar::Room as usize]?);
+        // versions without a build string are matched blindly, don't trust any offset that doesn't lead to a room ID
+        if !room_check.update(room.deref::<i32>(process).ok()) {
+            return None;
+        }
+        let run_end_flag = RunEndFlagPointer::new_32bit(*module_offset, gv.offsets[GameVar::RunEndFlag as usize]?);
+        let in_game_time = gv.offsets[GameVar::InGameTime as usize].map(|path| IGTPointer::new_32bit(*module_offset, path));
+        let survivor = gv.offsets[GameVar::Survivor as usize].map(|path| SurvivorPointer::new_32bit(*module_offset, path));
+        let difficulty = gv.offsets[GameVar::Difficulty as usize].map(|path| DifficultyPointer::new_32bit(*module_offset, path));
+        return Some((room, run_end_flag, in_game_time, survivor, difficulty));
+    }
+
+    /// Trusts a blindly matched room pointer once it reads the same room, other than rInit, for `STABLE_ROOM_TICKS` ticks in a row
+    ///
+    /// rInit is room 0, which is also what a wrong offset into zeroed memory reads
+    #[derive(Default)]
+    pub struct RoomCheck {
+        /// Last read, None when it was not a room ID
+        room: Option<i32>,
+        /// Ticks in a row `room` has been read
+        ticks: u32,
+    }
+
+    impl RoomCheck {
+        /// Adds a tick's room read, true once the room pointer can be trusted
+        pub fn update(&mut self, read: Option<i32>) -> bool {
+            let read = read.filter(|room| (0..=LAST_ROOM).contains(room));
+            self.ticks = if read.is_some() && read == self.room { self.ticks + 1 } else { 1 };
+            self.room = read;
+            return self.room.is_some_and(|room| room != Room::rInit as i32) && self.ticks >= STABLE_ROOM_TICKS;
+        }
+
+        /// True when the last read was not a room ID at all, as opposed to a room that hasn't been stable for long enough
+        pub fn unknown(&self) -> bool { return self.room.is_none(); }
+    }
+
+    /// Detected version name and the pointer path of every game var
+    pub fn version_diagnostics(process: &Process, module_offset: &Address, module_size: u64) -> Option<(&'static str, Vec<NamedPath>)> {
+        let gv = VERSIONS.detect(process, module_offset, module_size)?;
+        return Some((gv.name, gv.named_paths(*module_offset, &GAME_VAR_NAMES)));
+    }
+
+    /// Prints the module size and any build string candidates, for adding an unsupported version
+    pub fn report_unknown_version(process: &Process, module_offset: &Address, module_size: u64) {
+        asr::print_message(&format!("Main module size: 0x{:X}", module_size));
+        VERSIONS.report_unknown(process, module_offset, module_size);
+    }
+
+// implementation details
+
+    /// Index of each game var in `GameVersionData::offsets`
+    enum GameVar {
+        Room,
+        RunEndFlag,
+        InGameTime,
+        Survivor,
+        Difficulty,
+    }
+
+    impl GameVar {
+        const COUNT: usize = 5;
+    }
+
+    /// Highest room ID in any supported version
+    const LAST_ROOM: i32 = Room::r6_1_1 as i32;
+
+    /// Ticks in a row the room has to read the same before a blindly matched version is trusted, half a second
+    const STABLE_ROOM_TICKS: u32 = 60;
+
+    /// Game var names for diagnostics, in `GameVar` order
+    const GAME_VAR_NAMES: [&str; GameVar::COUNT] = ["room", "run_end_flag", "in_game_time", "survivor", "difficulty"];
+
+    /// Known RoR1 builds, matched in order
+    ///
+    /// RoR1 has no build string, builds are told apart by their main module size instead, which also differs between
+    /// the Windows executable and the one run under Linux/Proton. To add a Linux/Proton offset set:
+    /// 1. enable "Print diagnostic messages to the runtime log" and attach, a build that fails the room check prints its "Main module size"
+    /// 2. locate the game vars in that build and add an entry above the fallback, with `module_size: Some(<printed size>)`
+    const VERSION_DATA: [GameVersionData<{GameVar::COUNT}>; 1] = [
+        // Fallback for every module size, it is assumed when attaching once the room reads as a stable room ID, see `RoomCheck`
+        { GameVersionData {
+            name: "1.2.2",
+            build_string: None,
+            module_size: None,
+            offsets: [
+                /* room */ Some(&[0x2BED7A8]),
+                /* run_end_flag */ Some(&[0x2BEB5E0, 0x0, 0x548, 0xC, 0xB4]),
+                /* in_game_time */ Some(&[0x02BEB5E0, 0x0, 0x28, 0xC, 0xBC, 0x8, 0x0, 0x720, 0x8, 0x1EC0]),
+                /* survivor */ None,
+                /* difficulty */ None,
+            ]
+        } },
+    ];
+
+    /// Holds static data for each game version the autosplitter supports
+    const VERSIONS: VersionTable<{GameVar::COUNT}> = VersionTable::new(&VERSION_DATA);
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::autosplitter::golden::{self, Reads, Replay};
+
+    fn settings(timing: Timing) -> GameSettings {
+        return GameSettings {
+            _ror1_settings: Title,
+            ror1_stages: true,
+            ror1_split_stage_1: true,
+            ror1_split_stage_2: true,
+            ror1_split_stage_3: true,
+            ror1_split_stage_4: true,
+            ror1_split_stage_5: true,
+            ror1_pigbeach: false,
+            ror1_reset_main_menu: true,
+            ror1_reset_online_lobby: true,
+            ror1_reset_local_lobby: false,
+            ror1_reset_on_restart: false,
+            ror1_timing: timing,
+            ror1_required_survivor: RequiredSurvivor::Any,
+            ror1_required_difficulty: RequiredDifficulty::Any,
+        };
+    }
+
+    fn new_game(timing: Timing) -> Game {
+        return Game { settings: settings(timing), game_state: GameVars::default(), diagnostics: game::Diagnostics::default() };
+    }
+
+    impl Replay for Game {
+        fn replay_tick(&mut self, reads: &Reads) {
+            self.game_state.room.update(reads.get("room"));
+            self.game_state.run_end_flag.update(reads.get("run_end_flag"));
+            self.game_state.in_game_time.update(reads.get("in_game_time"));
+            self.update_stage();
+            self.update_survivor(reads.get("survivor"));
+            self.update_difficulty(reads.get("difficulty"));
+            self.update_last_in_game_time();
+            self.update_final_in_game_time();
+        }
+    }
+
+    /// One tick of reads, updated in the order `attached` updates them
+    fn tick(game: &mut Game, room: Room, run_end_flag: Option<i32>, in_game_time: Option<f64>) {
+        game.game_state.room.update(Some(room as i32));
+        game.game_state.run_end_flag.update(run_end_flag);
+        game.game_state.in_game_time.update(in_game_time);
+        game.update_stage();
+        game.update_last_in_game_time();
+        game.update_final_in_game_time();
+    }
+
+    #[test]
+    fn final_igt_is_kept_from_completion_until_reset() {
+        let mut game = new_game(Timing::RealTime);
+        tick(&mut game, Room::r5_1_1, None, Some(1400.0));
+        tick(&mut game, Room::r6_1_1, Some(0), Some(1500.0));
+        tick(&mut game, Room::r6_1_1, Some(0), Some(1830.5));
+        assert!(game.final_igt().is_none());
+
+        tick(&mut game, Room::r6_1_1, Some(1), Some(1831.0));
+        assert!(game.completed());
+        assert_eq!(game.final_igt().as_deref(), Some("00:30:31"));
+        // Time Alive is invalid through the outro and credits
+        for room in [Room::r2Cutscene2, Room::r2Cutscene3, Room::rCredits, Room::rStart] {
+            tick(&mut game, room, None, None);
+        }
+        assert_eq!(game.final_igt().as_deref(), Some("00:30:31"));
+
+        game.soft_reset();
+        assert!(game.final_igt().is_none());
+    }
+
+    #[test]
+    fn real_time_has_no_load_removal() {
+        let game = new_game(Timing::RealTime);
+        assert!(game.load_strategy() == game::LoadStrategy::None);
+    }
+
+    #[test]
+    fn in_game_time_syncs_to_time_alive() {
+        let game = new_game(Timing::InGameTime);
+        assert!(game.load_strategy() == game::LoadStrategy::InGameTime);
+    }
+
+    #[test]
+    fn game_time_holds_the_last_valid_time_alive() {
+        for timing in [Timing::RealTime, Timing::InGameTime] {
+            let mut game = new_game(timing);
+            assert_eq!(game.game_time(), None);
+
+            game.game_state.in_game_time.update(Some(12.5));
+            game.update_last_in_game_time();
+            assert_eq!(game.game_time(), Some(Duration::seconds_f64(12.5)));
+
+            // briefly invalid at the end of a run
+            game.game_state.in_game_time.update(Some(f64::NAN));
+            game.update_last_in_game_time();
+            game.game_state.in_game_time.update(None);
+            game.update_last_in_game_time();
+            assert_eq!(game.game_time(), Some(Duration::seconds_f64(12.5)));
+        }
+    }
+
+    #[test]
+    fn starts_when_leaving_a_lobby_for_a_stage() {
+        for lobby in LOBBY_ROOMS {
+            assert!(evaluate_start(lobby, Room::r1_1_1 as i32));
+            assert!(evaluate_start(lobby, Room::r1_2_3 as i32));
+        }
+    }
+
+    #[test]
+    fn never_starts_outside_of_leaving_a_lobby() {
+        // backing out to rHost or the main menu
+        assert!(!evaluate_start(Room::rSelectMult as i32, Room::rHost as i32));
+        assert!(!evaluate_start(Room::rSelect as i32, Room::rStart as i32));
+        // moving between lobbies, or staying in one
+        assert!(!evaluate_start(Room::rSelect as i32, Room::rSelectCoop as i32));
+        assert!(!evaluate_start(Room::rSelect as i32, Room::rSelect as i32));
+        // stage to stage and menu to stage
+        assert!(!evaluate_start(Room::r1_1_1 as i32, Room::r2_1_1 as i32));
+        assert!(!evaluate_start(Room::rStart as i32, Room::r1_1_1 as i32));
+    }
+
+    /// Leaves a lobby for Stage 1 with `survivor` as the run's survivor, returns whether the run starts
+    fn starts_with(game: &mut Game, survivor: Option<i32>) -> bool {
+        for room in [Room::rSelect, Room::r1_1_1] {
+            game.game_state.room.update(Some(room as i32));
+            game.update_survivor(survivor);
+        }
+        return game.start();
+    }
+
+    #[test]
+    fn start_gates_on_the_required_survivor() {
+        let mut game = new_game(Timing::RealTime);
+        game.settings.ror1_required_survivor = RequiredSurvivor::Huntress;
+        assert!(starts_with(&mut game, Some(RequiredSurvivor::Huntress.index().unwrap())));
+
+        let mut game = new_game(Timing::RealTime);
+        game.settings.ror1_required_survivor = RequiredSurvivor::Huntress;
+        assert!(!starts_with(&mut game, Some(RequiredSurvivor::Commando.index().unwrap())));
+    }
+
+    #[test]
+    fn start_allows_any_survivor() {
+        for survivor in [Some(0), Some(11), None] {
+            assert!(starts_with(&mut new_game(Timing::RealTime), survivor));
+        }
+        // versions where the survivor has not been located never block the start
+        let mut game = new_game(Timing::RealTime);
+        game.settings.ror1_required_survivor = RequiredSurvivor::Huntress;
+        assert!(starts_with(&mut game, None));
+    }
+
+    #[test]
+    fn survivor_is_invalid_outside_of_a_run() {
+        let mut game = new_game(Timing::RealTime);
+        for room in [Room::rStart, Room::rSelect, Room::rSelectCoop, Room::rSelectMult] {
+            game.game_state.room.update(Some(room as i32));
+            game.update_survivor(Some(3));
+            assert!(game.game_state.survivor.pair.is_none());
+        }
+        game.game_state.room.update(Some(Room::r1_1_1 as i32));
+        game.update_survivor(Some(3));
+        assert_eq!(game.game_state.survivor.pair.map(|survivor| survivor.current), Some(3));
+    }
+
+    #[test]
+    fn start_gates_on_the_required_difficulty() {
+        let enter_run = |game: &mut Game, difficulty: Option<i32>| {
+            for room in [Room::rSelect, Room::r1_1_1] {
+                game.game_state.room.update(Some(room as i32));
+                game.update_difficulty(difficulty);
+            }
+            // invalid in the lobby, the level selected there is read on the first tick of the run
+            return game.start();
+        };
+
+        let mut game = new_game(Timing::RealTime);
+        game.settings.ror1_required_difficulty = RequiredDifficulty::Monsoon;
+        assert!(enter_run(&mut game, Some(3)));
+
+        let mut game = new_game(Timing::RealTime);
+        game.settings.ror1_required_difficulty = RequiredDifficulty::Monsoon;
+        assert!(!enter_run(&mut game, Some(1)));
+
+        // any difficulty, or one that has not been located, never blocks the start
+        assert!(enter_run(&mut new_game(Timing::RealTime), Some(1)));
+        let mut game = new_game(Timing::RealTime);
+        game.settings.ror1_required_difficulty = RequiredDifficulty::Monsoon;
+        assert!(enter_run(&mut game, None));
+    }
+
+    /// Tick on which `RoomCheck` first trusts the room pointer
+    fn trusted_after(reads: impl IntoIterator<Item = Option<i32>>) -> Option<usize> {
+        let mut room_check = RoomCheck::default();
+        return reads.into_iter().position(|read| room_check.update(read)).map(|tick| tick + 1);
+    }
+
+    /// A second of the same read
+    fn second_of(read: Option<i32>) -> impl Iterator<Item = Option<i32>> {
+        return std::iter::repeat(read).take(120);
+    }
+
+    #[test]
+    fn room_pointer_needs_a_stable_known_room() {
+        // zeroed memory reads as rInit, and garbage as anything
+        assert_eq!(trusted_after(second_of(Some(Room::rInit as i32))), None);
+        assert_eq!(trusted_after(second_of(None)), None);
+        assert_eq!(trusted_after(second_of(Some(-1))), None);
+        // past r6_1_1
+        assert_eq!(trusted_after(second_of(Some(42))), None);
+        assert_eq!(trusted_after((0..120).map(|tick| Some(Room::rStart as i32 + tick % 2))), None);
+
+        let ticks = trusted_after(second_of(Some(Room::rStart as i32))).unwrap();
+        assert!(ticks > 1);
+        // booting through rInit and rLogo is trusted once the main menu settles
+        let boot = [Some(Room::rInit as i32), Some(Room::rLogo as i32)];
+        assert_eq!(trusted_after(boot.into_iter().chain(second_of(Some(Room::rStart as i32)))), Some(ticks + 2));
+        // attaching mid-run
+        assert_eq!(trusted_after(second_of(Some(Room::r2_1_1 as i32))), Some(ticks));
+        // a bad read starts over
+        let flicker = [Some(Room::rStart as i32), None];
+        assert_eq!(trusted_after(flicker.into_iter().chain(second_of(Some(Room::rStart as i32)))), Some(ticks + 2));
+    }
+
+    #[test]
+    fn difficulty_is_invalid_in_menus_and_lobbies() {
+        let mut game = new_game(Timing::RealTime);
+        for room in [Room::rStart, Room::rSelect] {
+            game.game_state.room.update(Some(room as i32));
+            game.update_difficulty(Some(2));
+            assert!(game.game_state.difficulty.pair.is_none());
+        }
+        assert_eq!(difficulty_name(2), "Rainstorm");
+    }
+
+    #[test]
+    fn every_required_survivor_has_a_name() {
+        for survivor in [RequiredSurvivor::Commando, RequiredSurvivor::HanD, RequiredSurvivor::Chef] {
+            assert_ne!(survivor_name(survivor.index().unwrap()), "Unknown");
+        }
+        assert_eq!(survivor_name(RequiredSurvivor::HanD.index().unwrap()), "HAN-D");
+        assert_eq!(survivor_name(-1), "Unknown");
+    }
+
+    #[test]
+    fn stage_of_groups_every_variant_into_its_stage() {
+        assert_eq!(stage_of(Room::r1_1_1 as i32), Some(1));
+        assert_eq!(stage_of(Room::r1_2_3 as i32), Some(1));
+        assert_eq!(stage_of(Room::r2_1_1 as i32), Some(2));
+        assert_eq!(stage_of(Room::r3_2_2 as i32), Some(3));
+        assert_eq!(stage_of(Room::r4_2_1 as i32), Some(4));
+        assert_eq!(stage_of(Room::r5_1_2 as i32), Some(5));
+        assert_eq!(stage_of(FINAL_ROOM), Some(6));
+    }
+
+    #[test]
+    fn stage_of_is_none_outside_of_stages() {
+        for room in MENU_ROOMS.iter().chain(LOBBY_ROOMS.iter()).chain(OUTRO_ROOMS.iter()) {
+            assert_eq!(stage_of(*room), None, "room {}", room);
+        }
+    }
+
+    #[test]
+    fn stages_only_change_on_stage_transitions() {
+        let mut game = new_game(Timing::RealTime);
+        let mut splits = 0;
+        for room in [Room::rSelect, Room::r1_1_2, Room::r2_2_1, Room::r3_1_2, Room::rPigbeach, Room::r3_2_1, Room::r4_1_1] {
+            game.game_state.room.update(Some(room as i32));
+            game.update_stage();
+            if game.split() {
+                splits += 1;
+            }
+        }
+        // Stage 1 -> 2, 2 -> 3, and 3 -> 4, the hidden stage and the variant change within Stage 3 never split
+        assert_eq!(splits, 3);
+    }
+
+    #[test]
+    fn golden_run() {
+        golden::replay(&mut new_game(Timing::RealTime), include_str!("golden/ror1.golden"));
+    }
+
+    #[test]
+    fn settings_schema_matches_game_settings() {
+        game::assert_schema_matches!(SETTINGS_SCHEMA, settings(Timing::RealTime), {
+            _ror1_settings, ror1_stages, ror1_split_stage_1, ror1_split_stage_2, ror1_split_stage_3, ror1_split_stage_4,
+            ror1_split_stage_5, ror1_pigbeach, ror1_reset_main_menu, ror1_reset_online_lobby, ror1_reset_local_lobby,
+            ror1_reset_on_restart, ror1_timing, ror1_required_survivor, ror1_required_difficulty,
+        });
+    }
+}
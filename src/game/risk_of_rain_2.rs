@@ -1,34 +1,714 @@
-use asr::{Address, game_engine::unity::{mono::Module, SceneManager, get_scene_name}, Error, future::{retry, next_tick}, PointerSize, Process, settings::{Gui, gui::Title}, string::{ArrayString}, watcher::Watcher};
+use asr::{Address, game_engine::unity::{mono::{Class, Image, Module}, SceneManager, get_scene_name}, Error, future::{retry, next_tick}, PointerSize, Process, settings::{Gui, gui::Title}, string::{ArrayString}, time::Duration, watcher::Watcher};
 use async_trait::async_trait;
 use bytemuck::CheckedBitPattern;
 use derive;
 
-use crate::game;
-use crate::AutoSplitter;
+use crate::game::{self, GameAutoSplitter};
+use crate::{autosplitter::TICK_RATE, AutoSplitter};
 
 const TARGET_PROCESS_NAME : &str = "Risk of Rain 2.exe";
 
+pub const INFO: game::GameInfo = game::GameInfo {
+    id: game::GameId::RiskOfRain2,
+    display_name: "Risk of Rain 2",
+    process_names: &[TARGET_PROCESS_NAME],
+    settings: SETTINGS_SCHEMA,
+};
+
+/// Number of cells in Void Fields
+const ARENA_ROUNDS : i32 = 9;
+
+/// Boss groups cleared in The Planetarium, one per Voidling phase
+const VOIDLING_PHASES : i32 = 3;
+
+/// TeleporterInteraction.ActivationState.Charging
+const TELEPORTER_CHARGING : u32 = 2;
+/// TeleporterInteraction.ActivationState.Charged
+const TELEPORTER_CHARGED : u32 = 3;
+
+/// Role of a scene in a run, see `SCENES`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SceneKind {
+    /// Regular Stage 1, runs start and loops return here
+    ///
+    /// Matched by prefix, variants (e.g. "golemplains2", "lakesnight") share the prefix
+    Stage1,
+    /// Side area with its own enter and exit splits
+    Special,
+    /// The run can end here
+    Ending,
+    /// Menu or lobby, resets the timer
+    Menu,
+}
+
+/// Every scene with a role in start, split, reset, or completion logic
+///
+/// Stage 1s: base game, Survivors of the Void ("snowyforest"), and Seekers of the Storm ("lakes", "village")
+const SCENES : [(&str, SceneKind); 20] = [
+    ("golemplains", SceneKind::Stage1),
+    ("blackbeach", SceneKind::Stage1),
+    ("snowyforest", SceneKind::Stage1),
+    ("lakes", SceneKind::Stage1),
+    ("village", SceneKind::Stage1),
+    ("bazaar", SceneKind::Special),
+    ("arena", SceneKind::Special),
+    ("goldshores", SceneKind::Special),
+    ("artifactworld", SceneKind::Special),
+    ("outro", SceneKind::Ending),
+    ("limbo", SceneKind::Ending),
+    ("mysteryspace", SceneKind::Ending),
+    ("voidraid", SceneKind::Ending),
+    ("loadingbasic", SceneKind::Menu),
+    ("intro", SceneKind::Menu),
+    ("title", SceneKind::Menu),
+    ("lobby", SceneKind::Menu),
+    ("crystalworld", SceneKind::Menu),
+    ("eclipseworld", SceneKind::Menu),
+    ("infinitetowerworld", SceneKind::Menu),
+];
+
+/// Capacity of a stored scene name, longer names are not stored
+///
+/// Fits the longest vanilla scene name ("infinitetowerworld") with room for modded scenes
+const SCENE_NAME_LEN : usize = 32;
+/// Capacity of a scene path read from the SceneManager, the name is its last component
+const SCENE_PATH_LEN : usize = 256;
+
+/// Seconds to wait for RoR2.dll after the scene is valid before falling back to Assembly-CSharp.dll
+const IMAGE_WAIT_SECONDS : f64 = 5.0;
+
+/// Seconds in a scene without any core class before the image is selected again
+const IMAGE_RETRY_SECONDS : f64 = 10.0;
+
+/// System.Collections.Generic.List<T>._items
+const LIST_ITEMS_OFFSET : u64 = 0x10;
+/// System.Collections.Generic.List<T>._size
+const LIST_SIZE_OFFSET : u64 = 0x18;
+/// Length of a managed array (T[])
+const ARRAY_LENGTH_OFFSET : u64 = 0x18;
+/// First element of a managed array (T[])
+const ARRAY_DATA_OFFSET : u64 = 0x20;
+/// System.String._stringLength
+const STRING_LENGTH_OFFSET : u64 = 0x10;
+/// System.String._firstChar, UTF-16
+const STRING_CHARS_OFFSET : u64 = 0x14;
+
+/// RoR2Content.Items fields of every item counted in the local inventory, see `item_index_path`
+///
+/// The Heresy items banned by some categories (Visions, Hooks, Strides, and Essence of Heresy),
+/// then the items routes can split on, in `RouteItem` order from `FIRST_ROUTE_ITEM`
+const TRACKED_ITEMS : [&str; 8] = [
+    "LunarPrimaryReplacement", "LunarSecondaryReplacement", "LunarUtilityReplacement", "LunarSpecialReplacement",
+    "LunarTrinket", "TitanGoldDuringTP", "Pearl", "ShinyPearl",
+];
+/// Index of the first route item in `TRACKED_ITEMS`
+const FIRST_ROUTE_ITEM : usize = 4;
+
 #[derive(Gui)]
 pub struct GameSettings {
     /// Risk of Rain 2 Settings
     pub _ror2_settings: Title,
+    /// Apply split presets for the detected run category
+    ///
+    /// Classic runs split on stage transitions, Simulacrum runs split on boss waves.
+    /// Presets only add splits, they never disable a split enabled below.
+    #[default = false]
+    pub category_presets: bool,
     /// Split on stage transitions
     ///
     /// This excludes selected hidden realms and game end conditions
     #[default = false]
     pub ror2_stages: bool,
+    /// Only split on stages not seen before this run
+    ///
+    /// For unique stage categories, stage transitions into a stage seen on an earlier loop do not split
+    #[default = false]
+    pub unique_stage_splits: bool,
+    /// Split on every loop
+    ///
+    /// Splits when entering a Stage 1 again later in the run. Already covered when splitting on stage transitions
+    #[default = false]
+    pub ror2_loop_splits: bool,
+    /// Arm automatic starts for Risk of Rain 2
+    ///
+    /// Disarm to start the timer manually, automatic splits and resets still apply
+    #[default = true]
+    pub arm_start: bool,
+    /// Also start when a new run is created
+    ///
+    /// For runs that do not begin with a Stage 1 fade-in, e.g. practice runs started on a custom stage
+    #[default = false]
+    pub ror2_start_on_run: bool,
+    /// Only start with this survivor
+    ///
+    /// Ignored when the survivor cannot be read
+    pub ror2_required_survivor: RequiredSurvivor,
+    /// Only start on this difficulty
+    ///
+    /// Eclipse covers every Eclipse level. Ignored when the difficulty cannot be read
+    pub ror2_require_difficulty: RequiredDifficulty,
+    /// Missed reads tolerated when detecting the Stage 1 fade-in start
+    ///
+    /// The fade is sometimes unreadable for a few ticks while it crosses, which can miss the start
+    pub fade_start_tolerance: FadeTolerance,
+    /// Settle an undetermined loading state after
+    ///
+    /// When the fade stays constant for this long, a partial fade counts as loading and no fade as not loading
+    pub undetermined_loading_timeout: LoadingTimeout,
+    /// Sync game time to the run timer
+    ///
+    /// Replaces fade based load removal while a run exists, outside of a run fade based load removal is used.
+    /// The run timer stops in the pause menu and whenever the in-game run timer stops.
+    #[default = false]
+    pub ror2_fixed_time_sync: bool,
+    /// Reset when returning to the character select lobby
+    ///
+    /// Disable for multi-game series that return to the lobby between runs, quitting to the title screen still resets
+    #[default = true]
+    pub reset_on_lobby: bool,
+    /// Reset when entering the Prismatic Trials menu
+    ///
+    /// Disable to keep timing from the Prismatic Trials menu. Like all automatic resets, this is disabled after the first split.
+    #[default = true]
+    pub reset_on_crystalworld: bool,
+    /// Reset when entering the Eclipse menu
+    ///
+    /// Disable to keep timing from the Eclipse menu. Like all automatic resets, this is disabled after the first split.
+    #[default = true]
+    pub reset_on_eclipseworld: bool,
+    /// Reset when entering the Simulacrum menu
+    ///
+    /// Disable to keep timing from the Simulacrum menu. Like all automatic resets, this is disabled after the first split.
+    #[default = true]
+    pub reset_on_infinitetowerworld: bool,
+    /// Reset when dying
+    ///
+    /// Only a game over resets, revives (Dio's Best Friend) do not.
+    /// Like all automatic resets, this is disabled after the first split.
+    #[default = false]
+    pub reset_on_death: bool,
+    /// Pause game time from the results screen until the next run starts
+    ///
+    /// Covers time spent on the results screen and in menus after any run end, including deaths
+    #[default = false]
+    pub pause_on_results: bool,
+    /// Split when entering Commencement
+    ///
+    /// Stage transitions never split on Commencement itself
+    #[default = false]
+    pub commencement_enter_split: bool,
+    /// Split when entering The Planetarium
+    #[default = false]
+    pub voidraid_enter_split: bool,
+    /// Voidling kill in The Planetarium
+    ///
+    /// The run otherwise completes on the results screen after the ending
+    pub ror2_voidling_kill: VoidlingKill,
+    /// Split when entering Bazaar Between Time
+    #[default = false]
+    pub bazaar_enter: bool,
     /// Split when leaving Bazaar Between Time
     #[default = false]
-    pub bazaar: bool,
+    pub bazaar_exit: bool,
+    /// Split on every purchase in Bazaar Between Time
+    ///
+    /// Any lunar coin purchase splits (shop, pods, Seers, rerolls), independently from leaving the Bazaar
+    #[default = false]
+    pub bazaar_purchase_splits: bool,
+    /// Split on every equipment use
+    #[default = false]
+    pub equipment_use_splits: bool,
+    /// Split when first picking up a red (legendary) item
+    ///
+    /// Splits once per run, later red items do not split
+    #[default = false]
+    pub ror2_first_red: bool,
+    /// Split when first picking up the route item below
+    ///
+    /// Splits once per run, further stacks or picking it up again later do not split
+    #[default = false]
+    pub split_on_item: bool,
+    /// Route item
+    pub split_item: RouteItem,
+    /// Split when entering Void Fields
+    #[default = false]
+    pub arena_enter: bool,
     /// Split when leaving Void Fields
     #[default = false]
-    pub arena: bool,
+    pub arena_exit: bool,
+    /// Split when clearing all cells in Void Fields
+    #[default = false]
+    pub void_fields_complete_split: bool,
+    /// Split on every cell captured in Void Fields
+    ///
+    /// The last cell splits once, even when clearing all cells is also enabled
+    #[default = false]
+    pub ror2_arena_cells: bool,
+    /// Split when entering Gilded Shores
+    #[default = false]
+    pub goldshores_enter: bool,
     /// Split when leaving Gilded Shores
     #[default = false]
-    pub goldshores: bool,
+    pub goldshores_exit: bool,
+    /// Split when entering Bulwark's Ambry
+    #[default = false]
+    pub artifactworld_enter: bool,
     /// Split when leaving Bulwark's Ambry
     #[default = false]
-    pub artifactworld: bool,
+    pub artifactworld_exit: bool,
+    /// Split when completing the Artifact Trial in Bulwark's Ambry
+    ///
+    /// Leaving the trial without claiming the artifact does not split
+    #[default = false]
+    pub artifactworld_on_success: bool,
+    /// Split when initiating the obliteration in A Moment, Fractured
+    ///
+    /// Splits when the screen starts fading out, before the run completes on the results screen
+    #[default = false]
+    pub obliterate_initiate_split: bool,
+    /// Split on every boss kill
+    ///
+    /// This includes teleporter bosses, Mithrix phases, and bosses from shrines or events
+    #[default = false]
+    pub any_boss_splits: bool,
+    /// Split when the teleporter is fully charged
+    ///
+    /// Splits once per teleporter, leaving the stage afterwards still splits separately on stage transitions
+    #[default = false]
+    pub ror2_teleporter: bool,
+    /// Split on each Mithrix phase in Commencement
+    ///
+    /// Splits when each phase of the fight starts, boss kill splits may split on the same phases
+    #[default = false]
+    pub ror2_mithrix_phases: bool,
+    /// Split on every completed Simulacrum wave
+    ///
+    /// The first wave starting is the start, the run ends on the results screen
+    #[default = false]
+    pub ror2_simulacrum_waves: bool,
+    /// Split once the total number of charged teleporters in a run reaches
+    pub split_on_teleporter_count: TeleporterCount,
+    /// Split every time gold first reaches a multiple of
+    ///
+    /// Each milestone splits once per run, even when gold drops below it and reaches it again
+    pub split_on_gold: GoldMilestone,
+    /// Split whenever the teleporter stops charging
+    ///
+    /// Charging pauses while no player is inside the teleporter radius
+    #[default = false]
+    pub teleporter_pause_splits: bool,
+    /// Split on every Shrine of the Mountain activated
+    ///
+    /// Each activation adds a boss wave to the teleporter event of the current stage
+    #[default = false]
+    pub ror2_mountain_shrines: bool,
+    /// Individual Stage Splits
+    ///
+    /// Split when entering specific stages, including when looping. Splitting on stage transitions already covers every stage
+    pub _ror2_stage_splits: Title,
+    /// Split when entering Titanic Plains
+    #[default = false]
+    pub split_golemplains: bool,
+    /// Split when entering Distant Roost
+    #[default = false]
+    pub split_blackbeach: bool,
+    /// Split when entering Siphoned Forest
+    #[default = false]
+    pub split_snowyforest: bool,
+    /// Split when entering Verdant Falls (including Viscous Falls)
+    #[default = false]
+    pub split_lakes: bool,
+    /// Split when entering Shattered Abodes (including Disturbed Impact)
+    #[default = false]
+    pub split_village: bool,
+    /// Split when entering Abandoned Aqueduct
+    #[default = false]
+    pub split_goolake: bool,
+    /// Split when entering Wetland Aspect
+    #[default = false]
+    pub split_foggyswamp: bool,
+    /// Split when entering Aphelian Sanctuary
+    #[default = false]
+    pub split_ancientloft: bool,
+    /// Split when entering Reformed Altar
+    #[default = false]
+    pub split_lemuriantemple: bool,
+    /// Split when entering Rallypoint Delta
+    #[default = false]
+    pub split_frozenwall: bool,
+    /// Split when entering Scorched Acres
+    #[default = false]
+    pub split_wispgraveyard: bool,
+    /// Split when entering Sulfur Pools
+    #[default = false]
+    pub split_sulfurpools: bool,
+    /// Split when entering Treeborn Colony (including Golden Dieback)
+    #[default = false]
+    pub split_habitat: bool,
+    /// Split when entering Abyssal Depths
+    #[default = false]
+    pub split_dampcavesimple: bool,
+    /// Split when entering Siren's Call
+    #[default = false]
+    pub split_shipgraveyard: bool,
+    /// Split when entering Sundered Grove
+    #[default = false]
+    pub split_rootjungle: bool,
+    /// Split when entering Sky Meadow
+    #[default = false]
+    pub split_skymeadow: bool,
+    /// Split when entering Helminth Hatchery
+    #[default = false]
+    pub split_helminthroost: bool,
+    /// Split when entering Prime Meridian
+    #[default = false]
+    pub split_meridian: bool,
+}
+
+/// Settings metadata for external configurators, keep in sync with `GameSettings`
+pub const SETTINGS_SCHEMA: &[game::SettingSchema] = &[
+    game::SettingSchema::bool("category_presets", "Apply split presets for the detected run category", false),
+    game::SettingSchema::bool("ror2_stages", "Split on stage transitions", false),
+    game::SettingSchema::bool("unique_stage_splits", "Only split on stages not seen before this run", false),
+    game::SettingSchema::bool("ror2_loop_splits", "Split on every loop", false),
+    game::SettingSchema::bool("arm_start", "Arm automatic starts for Risk of Rain 2", true),
+    game::SettingSchema::bool("ror2_start_on_run", "Also start when a new run is created", false),
+    game::SettingSchema::choice("ror2_required_survivor", "Only start with this survivor", "Any", &["Any", "Commando", "Huntress", "Bandit", "MulT", "Engineer", "Artificer", "Mercenary", "Rex", "Loader", "Acrid", "Captain", "Railgunner", "VoidFiend", "Seeker", "FalseSon", "Chef"]),
+    game::SettingSchema::choice("ror2_require_difficulty", "Only start on this difficulty", "Any", &["Any", "Drizzle", "Rainstorm", "Monsoon", "Eclipse"]),
+    game::SettingSchema::choice("fade_start_tolerance", "Missed reads tolerated when detecting the Stage 1 fade-in start", "Strict", &["Strict", "Short", "Long"]),
+    game::SettingSchema::choice("undetermined_loading_timeout", "Settle an undetermined loading state after", "Never", &["Never", "HalfSecond", "TwoSeconds"]),
+    game::SettingSchema::bool("ror2_fixed_time_sync", "Sync game time to the run timer", false),
+    game::SettingSchema::bool("reset_on_lobby", "Reset when returning to the character select lobby", true),
+    game::SettingSchema::bool("reset_on_crystalworld", "Reset when entering the Prismatic Trials menu", true),
+    game::SettingSchema::bool("reset_on_eclipseworld", "Reset when entering the Eclipse menu", true),
+    game::SettingSchema::bool("reset_on_infinitetowerworld", "Reset when entering the Simulacrum menu", true),
+    game::SettingSchema::bool("reset_on_death", "Reset when dying", false),
+    game::SettingSchema::bool("pause_on_results", "Pause game time from the results screen until the next run starts", false),
+    game::SettingSchema::bool("commencement_enter_split", "Split when entering Commencement", false),
+    game::SettingSchema::bool("voidraid_enter_split", "Split when entering The Planetarium", false),
+    game::SettingSchema::choice("ror2_voidling_kill", "Voidling kill in The Planetarium", "ResultsScreen", &["ResultsScreen", "Split", "Complete"]),
+    game::SettingSchema::bool("bazaar_enter", "Split when entering Bazaar Between Time", false),
+    game::SettingSchema::bool("bazaar_exit", "Split when leaving Bazaar Between Time", false),
+    game::SettingSchema::bool("bazaar_purchase_splits", "Split on every purchase in Bazaar Between Time", false),
+    game::SettingSchema::bool("equipment_use_splits", "Split on every equipment use", false),
+    game::SettingSchema::bool("ror2_first_red", "Split when first picking up a red (legendary) item", false),
+    game::SettingSchema::bool("split_on_item", "Split when first picking up the route item below", false),
+    game::SettingSchema::choice("split_item", "Route item", "BeadsOfFealty", &["BeadsOfFealty", "HalcyonSeed", "Pearl", "IrradiantPearl"]),
+    game::SettingSchema::bool("arena_enter", "Split when entering Void Fields", false),
+    game::SettingSchema::bool("arena_exit", "Split when leaving Void Fields", false),
+    game::SettingSchema::bool("void_fields_complete_split", "Split when clearing all cells in Void Fields", false),
+    game::SettingSchema::bool("ror2_arena_cells", "Split on every cell captured in Void Fields", false),
+    game::SettingSchema::bool("goldshores_enter", "Split when entering Gilded Shores", false),
+    game::SettingSchema::bool("goldshores_exit", "Split when leaving Gilded Shores", false),
+    game::SettingSchema::bool("artifactworld_enter", "Split when entering Bulwark's Ambry", false),
+    game::SettingSchema::bool("artifactworld_exit", "Split when leaving Bulwark's Ambry", false),
+    game::SettingSchema::bool("artifactworld_on_success", "Split when completing the Artifact Trial in Bulwark's Ambry", false),
+    game::SettingSchema::bool("obliterate_initiate_split", "Split when initiating the obliteration in A Moment, Fractured", false),
+    game::SettingSchema::bool("any_boss_splits", "Split on every boss kill", false),
+    game::SettingSchema::bool("ror2_teleporter", "Split when the teleporter is fully charged", false),
+    game::SettingSchema::bool("ror2_mithrix_phases", "Split on each Mithrix phase in Commencement", false),
+    game::SettingSchema::bool("ror2_simulacrum_waves", "Split on every completed Simulacrum wave", false),
+    game::SettingSchema::choice("split_on_teleporter_count", "Split once the total number of charged teleporters in a run reaches", "Off", &["Off", "Five", "Ten", "Fifteen", "Twenty"]),
+    game::SettingSchema::choice("split_on_gold", "Split every time gold first reaches a multiple of", "Off", &["Off", "OneThousand", "FiveThousand", "TenThousand", "FiftyThousand"]),
+    game::SettingSchema::bool("teleporter_pause_splits", "Split whenever the teleporter stops charging", false),
+    game::SettingSchema::bool("ror2_mountain_shrines", "Split on every Shrine of the Mountain activated", false),
+    game::SettingSchema::bool("split_golemplains", "Split when entering Titanic Plains", false),
+    game::SettingSchema::bool("split_blackbeach", "Split when entering Distant Roost", false),
+    game::SettingSchema::bool("split_snowyforest", "Split when entering Siphoned Forest", false),
+    game::SettingSchema::bool("split_lakes", "Split when entering Verdant Falls (including Viscous Falls)", false),
+    game::SettingSchema::bool("split_village", "Split when entering Shattered Abodes (including Disturbed Impact)", false),
+    game::SettingSchema::bool("split_goolake", "Split when entering Abandoned Aqueduct", false),
+    game::SettingSchema::bool("split_foggyswamp", "Split when entering Wetland Aspect", false),
+    game::SettingSchema::bool("split_ancientloft", "Split when entering Aphelian Sanctuary", false),
+    game::SettingSchema::bool("split_lemuriantemple", "Split when entering Reformed Altar", false),
+    game::SettingSchema::bool("split_frozenwall", "Split when entering Rallypoint Delta", false),
+    game::SettingSchema::bool("split_wispgraveyard", "Split when entering Scorched Acres", false),
+    game::SettingSchema::bool("split_sulfurpools", "Split when entering Sulfur Pools", false),
+    game::SettingSchema::bool("split_habitat", "Split when entering Treeborn Colony (including Golden Dieback)", false),
+    game::SettingSchema::bool("split_dampcavesimple", "Split when entering Abyssal Depths", false),
+    game::SettingSchema::bool("split_shipgraveyard", "Split when entering Siren's Call", false),
+    game::SettingSchema::bool("split_rootjungle", "Split when entering Sundered Grove", false),
+    game::SettingSchema::bool("split_skymeadow", "Split when entering Sky Meadow", false),
+    game::SettingSchema::bool("split_helminthroost", "Split when entering Helminth Hatchery", false),
+    game::SettingSchema::bool("split_meridian", "Split when entering Prime Meridian", false),
+];
+
+#[derive(Gui, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum FadeTolerance {
+    /// None (consecutive reads)
+    #[default]
+    Strict,
+    /// 50 milliseconds
+    Short,
+    /// 250 milliseconds
+    Long,
+}
+
+impl game::Choice for FadeTolerance {
+    const OPTIONS: &'static [Self] = &[Self::Strict, Self::Short, Self::Long];
+}
+
+impl FadeTolerance {
+    /// Tolerated update loop ticks without a valid fade read
+    fn ticks(self) -> u32 {
+        let seconds = match self {
+            FadeTolerance::Strict => return 0,
+            FadeTolerance::Short => 0.05,
+            FadeTolerance::Long => 0.25,
+        };
+        return (seconds * TICK_RATE) as u32;
+    }
+}
+
+#[derive(Gui, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum LoadingTimeout {
+    /// Never (keep the last known state)
+    #[default]
+    Never,
+    /// 0.5 seconds
+    HalfSecond,
+    /// 2 seconds
+    TwoSeconds,
+}
+
+impl game::Choice for LoadingTimeout {
+    const OPTIONS: &'static [Self] = &[Self::Never, Self::HalfSecond, Self::TwoSeconds];
+}
+
+impl LoadingTimeout {
+    /// Timeout in update loop ticks
+    fn ticks(self) -> Option<u32> {
+        let seconds = match self {
+            LoadingTimeout::Never => return None,
+            LoadingTimeout::HalfSecond => 0.5,
+            LoadingTimeout::TwoSeconds => 2.0,
+        };
+        return Some((seconds * TICK_RATE) as u32);
+    }
+}
+
+#[derive(Gui, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum RequiredDifficulty {
+    /// Any
+    #[default]
+    Any,
+    /// Drizzle
+    Drizzle,
+    /// Rainstorm
+    Rainstorm,
+    /// Monsoon
+    Monsoon,
+    /// Eclipse (any level)
+    Eclipse,
+}
+
+impl game::Choice for RequiredDifficulty {
+    const OPTIONS: &'static [Self] = &[Self::Any, Self::Drizzle, Self::Rainstorm, Self::Monsoon, Self::Eclipse];
+}
+
+impl RequiredDifficulty {
+    /// Whether the DifficultyIndex matches, Any matches every difficulty
+    fn matches(self, difficulty: i32) -> bool {
+        return match self {
+            RequiredDifficulty::Any => true,
+            RequiredDifficulty::Drizzle => difficulty == 0,
+            RequiredDifficulty::Rainstorm => difficulty == 1,
+            RequiredDifficulty::Monsoon => difficulty == 2,
+            RequiredDifficulty::Eclipse => (3..=10).contains(&difficulty),
+        };
+    }
+}
+
+#[derive(Gui, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum RequiredSurvivor {
+    /// Any
+    #[default]
+    Any,
+    /// Commando
+    Commando,
+    /// Huntress
+    Huntress,
+    /// Bandit
+    Bandit,
+    /// MUL-T
+    MulT,
+    /// Engineer
+    Engineer,
+    /// Artificer
+    Artificer,
+    /// Mercenary
+    Mercenary,
+    /// REX
+    Rex,
+    /// Loader
+    Loader,
+    /// Acrid
+    Acrid,
+    /// Captain
+    Captain,
+    /// Railgunner
+    Railgunner,
+    /// Void Fiend
+    VoidFiend,
+    /// Seeker
+    Seeker,
+    /// False Son
+    FalseSon,
+    /// CHEF
+    Chef,
+}
+
+impl game::Choice for RequiredSurvivor {
+    const OPTIONS: &'static [Self] = &[
+        Self::Any,
+        Self::Commando,
+        Self::Huntress,
+        Self::Bandit,
+        Self::MulT,
+        Self::Engineer,
+        Self::Artificer,
+        Self::Mercenary,
+        Self::Rex,
+        Self::Loader,
+        Self::Acrid,
+        Self::Captain,
+        Self::Railgunner,
+        Self::VoidFiend,
+        Self::Seeker,
+        Self::FalseSon,
+        Self::Chef,
+    ];
+}
+
+impl RequiredSurvivor {
+    /// BodyCatalog body name, None for any survivor
+    fn body_name(self) -> Option<&'static str> {
+        return match self {
+            RequiredSurvivor::Any => None,
+            RequiredSurvivor::Commando => Some("CommandoBody"),
+            RequiredSurvivor::Huntress => Some("HuntressBody"),
+            RequiredSurvivor::Bandit => Some("Bandit2Body"),
+            RequiredSurvivor::MulT => Some("ToolbotBody"),
+            RequiredSurvivor::Engineer => Some("EngiBody"),
+            RequiredSurvivor::Artificer => Some("MageBody"),
+            RequiredSurvivor::Mercenary => Some("MercBody"),
+            RequiredSurvivor::Rex => Some("TreebotBody"),
+            RequiredSurvivor::Loader => Some("LoaderBody"),
+            RequiredSurvivor::Acrid => Some("CrocoBody"),
+            RequiredSurvivor::Captain => Some("CaptainBody"),
+            RequiredSurvivor::Railgunner => Some("RailgunnerBody"),
+            RequiredSurvivor::VoidFiend => Some("VoidSurvivorBody"),
+            RequiredSurvivor::Seeker => Some("SeekerBody"),
+            RequiredSurvivor::FalseSon => Some("FalseSonBody"),
+            RequiredSurvivor::Chef => Some("ChefBody"),
+        };
+    }
+}
+
+#[derive(Gui, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum RouteItem {
+    /// Beads of Fealty
+    #[default]
+    BeadsOfFealty,
+    /// Halcyon Seed
+    HalcyonSeed,
+    /// Pearl
+    Pearl,
+    /// Irradiant Pearl
+    IrradiantPearl,
+}
+
+impl game::Choice for RouteItem {
+    const OPTIONS: &'static [Self] = &[Self::BeadsOfFealty, Self::HalcyonSeed, Self::Pearl, Self::IrradiantPearl];
+}
+
+impl RouteItem {
+    /// Index into the route items of `TRACKED_ITEMS`, counted from `FIRST_ROUTE_ITEM`
+    fn index(self) -> usize {
+        return match self {
+            RouteItem::BeadsOfFealty => 0,
+            RouteItem::HalcyonSeed => 1,
+            RouteItem::Pearl => 2,
+            RouteItem::IrradiantPearl => 3,
+        };
+    }
+}
+
+#[derive(Gui, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum VoidlingKill {
+    /// Complete on the results screen
+    #[default]
+    ResultsScreen,
+    /// Split
+    Split,
+    /// Complete the run
+    Complete,
+}
+
+impl game::Choice for VoidlingKill {
+    const OPTIONS: &'static [Self] = &[Self::ResultsScreen, Self::Split, Self::Complete];
+}
+
+#[derive(Gui, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum GoldMilestone {
+    /// Off
+    #[default]
+    Off,
+    /// 1,000 gold
+    OneThousand,
+    /// 5,000 gold
+    FiveThousand,
+    /// 10,000 gold
+    TenThousand,
+    /// 50,000 gold
+    FiftyThousand,
+}
+
+impl game::Choice for GoldMilestone {
+    const OPTIONS: &'static [Self] = &[Self::Off, Self::OneThousand, Self::FiveThousand, Self::TenThousand, Self::FiftyThousand];
+}
+
+impl GoldMilestone {
+    fn step(self) -> Option<u32> {
+        return match self {
+            GoldMilestone::Off => None,
+            GoldMilestone::OneThousand => Some(1_000),
+            GoldMilestone::FiveThousand => Some(5_000),
+            GoldMilestone::TenThousand => Some(10_000),
+            GoldMilestone::FiftyThousand => Some(50_000),
+        };
+    }
+}
+
+#[derive(Gui, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum TeleporterCount {
+    /// Off
+    #[default]
+    Off,
+    /// 5 teleporters
+    Five,
+    /// 10 teleporters
+    Ten,
+    /// 15 teleporters
+    Fifteen,
+    /// 20 teleporters
+    Twenty,
+}
+
+impl game::Choice for TeleporterCount {
+    const OPTIONS: &'static [Self] = &[Self::Off, Self::Five, Self::Ten, Self::Fifteen, Self::Twenty];
+}
+
+impl TeleporterCount {
+    fn target(self) -> Option<i32> {
+        return match self {
+            TeleporterCount::Off => None,
+            TeleporterCount::Five => Some(5),
+            TeleporterCount::Ten => Some(10),
+            TeleporterCount::Fifteen => Some(15),
+            TeleporterCount::Twenty => Some(20),
+        };
+    }
 }
 
 /// Game state watchers
@@ -38,17 +718,230 @@ pub struct GameVars {
     ///
     /// Value goes from 0.0->2.0 just before and during loads, then 2.0->0.0.
     pub fade: Watcher<f32>,
+    /// Ticks without a valid fade read since the fade was last at or above 1.0
+    ///
+    /// None once the fade has been read below 1.0.
+    pub ticks_since_opaque: Option<u32>,
+    /// The fade crossed below 1.0 this tick, within the configured tolerance
+    pub fade_in_crossed: bool,
+    /// Consecutive ticks the fade has not determined the loading state
+    pub undetermined_fade_ticks: u32,
     /// Run.instance.stageClearCount
     ///
     /// Starts at 0 and increments on every regular stage, including after Commencement at the end of a run.
     /// Does not increment on special stages like Bazaar.
     pub stage_count: Watcher<i32>,
+    /// Run.instance
+    ///
+    /// Changes when a new run is created, used to re-seed `stage_count`.
+    pub run_instance: Watcher<Address>,
+    /// `stage_count` became valid at 0 this tick, a new run was just created
+    pub run_created: bool,
     /// GameOverController.instance.shouldDisplayGameEndReportPanels
     ///
     /// Invalid until a game end condition is reached (includes dying).
     pub results: Watcher<bool>,
+    /// ArtifactTrialMissionController.instance.missionCompleted
+    ///
+    /// Only valid in Bulwark's Ambry, becomes true once the artifact has been claimed.
+    pub artifact_trial_complete: Watcher<bool>,
+    /// BossGroup.instancesList.Count
+    ///
+    /// Number of boss groups currently alive, decrements when a boss (group) is killed.
+    /// Regular monsters are never part of a BossGroup.
+    pub boss_groups: Watcher<i32>,
+    /// TeleporterInteraction.instance.isCharged
+    ///
+    /// Derived from activationStateInternal (Charged = 3, Finished = 4).
+    /// Invalid in the lobby and on stages without a teleporter.
+    pub teleporter_charged: Watcher<bool>,
+    /// TeleporterInteraction.instance.holdoutZoneController.isAnyoneCharging
+    ///
+    /// Only valid while the teleporter is charging, false while the charge is paused.
+    pub teleporter_charging: Watcher<bool>,
+    /// TeleporterInteraction.instance.shrineBonusStacks
+    ///
+    /// Shrines of the Mountain activated on the current stage.
+    /// Invalid outside of a run and on stages without a teleporter.
+    pub mountain_shrines: Watcher<i32>,
+    /// Teleporters charged so far this run, 0 outside of a run
+    pub teleporter_count: i32,
+    /// ArenaMissionController.instance.clearedRounds
+    ///
+    /// Only valid in Void Fields, counts captured cells up to 9.
+    pub arena_cleared_rounds: Watcher<i32>,
+    /// NetworkUser.localPlayers[0].netLunarCoins
+    ///
+    /// Lunar coins of the first local player, invalid on the title screen.
+    pub lunar_coins: Watcher<u32>,
+    /// PlayerCharacterMasterController.instances[0].body.equipmentSlot.stock
+    ///
+    /// Equipment charges of the first local player, decrements on every use.
+    /// Invalid outside of a run and while the player has no body.
+    pub equipment_stock: Watcher<i32>,
+    /// LocalUserManager.localUsersList[0].cachedMaster.lostBodyToDeath
+    ///
+    /// The local player died and was not revived. Invalid outside of a run.
+    pub local_player_dead: Watcher<bool>,
+    /// LocalUserManager.localUsersList[0].cachedMaster.money
+    ///
+    /// Gold of the local player. Invalid in the lobby and outside of a run.
+    pub gold: Watcher<u32>,
+    /// Highest gold milestone reached this run, 0 outside of a run
+    ///
+    /// Counted in steps of the selected milestone, starts over when another milestone is selected.
+    pub gold_milestones: u32,
+    /// Gold milestone the `gold_milestones` count was made for
+    pub gold_milestone_choice: Option<GoldMilestone>,
+    /// A new gold milestone was reached this tick
+    pub gold_milestone_reached: bool,
+    /// PlayerCharacterMasterController.instances.Count
+    ///
+    /// Players in the run, more than 1 in multiplayer. Invalid outside of a run.
+    pub player_count: Watcher<i32>,
+    /// InfiniteTowerRun.instance.waveIndex
+    ///
+    /// Only valid on Simulacrum stages, becomes 1 when the first wave starts.
+    pub simulacrum_wave: Watcher<i32>,
+    /// Run.instance.fixedTime
+    ///
+    /// Run clock advanced by the physics update, invalid outside of a run.
+    pub run_fixed_time: Watcher<f32>,
+    /// Run.instance.time
+    ///
+    /// Run clock advanced by the frame update, drifts from `run_fixed_time` under lag.
+    pub run_time: Watcher<f32>,
+    /// Run.instance.GetRunStopwatch()
+    ///
+    /// The in-game run timer, derived from `runStopwatch` and `run_fixed_time`. Invalid outside of a run.
+    pub run_stopwatch: Watcher<f32>,
+    /// PhaseCounter.instance.phase
+    ///
+    /// Only valid in Commencement, increments as each phase of the Mithrix fight starts.
+    pub mithrix_phase: Watcher<i32>,
+    /// BodyCatalog.bodyNames[NetworkUser.localPlayers[0].bodyIndexPreference]
+    ///
+    /// Body name of the survivor picked by the first local player (e.g. "CommandoBody").
+    /// Invalid on the title screen and in the lobby before a survivor is picked.
+    pub survivor: Watcher<ArrayString<32>>,
+    /// Run.instance.selectedDifficultyInternal
+    ///
+    /// DifficultyIndex of the run (0 = Drizzle, 1 = Rainstorm, 2 = Monsoon, 3-10 = Eclipse 1-8).
+    /// Invalid in the lobby, only valid once a run has started.
+    pub difficulty: Watcher<i32>,
+    /// Lunar coin purchases made during the current Bazaar visit
+    pub bazaar_purchases: i32,
+    /// The first local player holds any Heresy item
+    ///
+    /// None outside of a run, or before the item catalog is initialized.
+    pub holds_heresy_item: Option<bool>,
+    /// Stacks of the selected route item held by the first local player
+    ///
+    /// None outside of a run, or before the item catalog is initialized. Starts over when another item is selected.
+    pub route_item: Watcher<i32>,
+    /// Route item the `route_item` watcher was read for
+    pub route_item_choice: Option<RouteItem>,
+    /// The route item was held at some point this run, cleared outside of a run
+    pub route_item_held: bool,
+    /// The route item was picked up for the first time this run on this tick
+    pub route_item_acquired: bool,
+    /// Total stacks of red (tier 3) items held by the first local player
+    ///
+    /// None outside of a run, or before the item catalog is initialized.
+    pub red_items: Watcher<i32>,
+    /// A red item was held at some point this run, cleared outside of a run
+    pub red_item_held: bool,
+    /// The first red item of this run was picked up on this tick
+    pub first_red_acquired: bool,
+    /// Voidling phases cleared during the current visit to The Planetarium
+    pub voidling_phases_cleared: i32,
+    /// The results screen was shown, cleared once the next run starts
+    pub run_ended: bool,
+    /// Stages seen this run (by in-game title, so variants count as the same stage), empty outside of a run
+    pub seen_stages: Vec<ArrayString<SCENE_NAME_LEN>>,
+    /// The current stage was already seen earlier in this run
+    pub repeat_stage: bool,
+    /// Times the run looped back to a Stage 1, 0 outside of a run
+    pub loop_count: i32,
     /// Unity scene name
-    pub scene: Watcher<ArrayString<16>>,
+    pub scene: Watcher<ArrayString<SCENE_NAME_LEN>>,
+}
+
+/// In-game title of a scene, unknown scenes pass through their internal name
+pub fn stage_display_name(scene: &str) -> &str {
+    return match scene {
+        "golemplains" | "golemplains2" => "Titanic Plains",
+        "blackbeach" | "blackbeach2" => "Distant Roost",
+        "snowyforest" => "Siphoned Forest",
+        "lakes" => "Verdant Falls",
+        "lakesnight" => "Viscous Falls",
+        "village" => "Shattered Abodes",
+        "villagenight" => "Disturbed Impact",
+        "goolake" => "Abandoned Aqueduct",
+        "foggyswamp" => "Wetland Aspect",
+        "ancientloft" => "Aphelian Sanctuary",
+        "lemuriantemple" => "Reformed Altar",
+        "frozenwall" => "Rallypoint Delta",
+        "wispgraveyard" => "Scorched Acres",
+        "sulfurpools" => "Sulfur Pools",
+        "habitat" => "Treeborn Colony",
+        "habitatfall" => "Golden Dieback",
+        "dampcavesimple" => "Abyssal Depths",
+        "shipgraveyard" => "Siren's Call",
+        "rootjungle" => "Sundered Grove",
+        "skymeadow" => "Sky Meadow",
+        "helminthroost" => "Helminth Hatchery",
+        "moon" | "moon2" => "Commencement",
+        "meridian" => "Prime Meridian",
+        "bazaar" => "Bazaar Between Time",
+        "arena" => "Void Fields",
+        "goldshores" => "Gilded Shores",
+        "artifactworld" => "Bulwark's Ambry",
+        "mysteryspace" => "A Moment, Fractured",
+        "limbo" => "A Moment, Whole",
+        "voidstage" => "Void Locus",
+        "voidraid" => "The Planetarium",
+        _ => scene
+    };
+}
+
+/// Run.GetRunStopwatch(), from the raw RunStopwatch struct and Run.fixedTime
+///
+/// A paused stopwatch holds its time in the offset, a running one counts from the fixed time clock
+fn run_stopwatch(stopwatch: Option<[u8; 8]>, fixed_time: Option<f32>) -> Option<f32> {
+    let stopwatch = stopwatch?;
+    let offset = f32::from_le_bytes([stopwatch[0], stopwatch[1], stopwatch[2], stopwatch[3]]);
+    let time = if stopwatch[4] != 0 { offset } else { fixed_time? + offset };
+    return Some(time).filter(|time| time.is_finite() && *time >= 0.0);
+}
+
+/// Role of a scene from `SCENES`, None for regular stages after Stage 1 and unknown scenes
+pub fn scene_kind(scene: &str) -> Option<SceneKind> {
+    return SCENES.iter()
+        .find(|&&(name, kind)| if kind == SceneKind::Stage1 { scene.starts_with(name) } else { scene == name })
+        .map(|&(_, kind)| kind);
+}
+
+/// Regular Stage 1 scene, including variants
+fn is_stage_one(scene: &str) -> bool {
+    return scene_kind(scene) == Some(SceneKind::Stage1);
+}
+
+/// Scene names are non-empty and only use letters, digits, and underscores
+///
+/// Vanilla scenes are all lowercase, modded scenes may use uppercase letters (e.g. "FBLScene")
+fn is_valid_scene_name(scene: &str) -> bool {
+    return !scene.is_empty() && scene.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_');
+}
+
+/// Updates a watcher from a single read, making old = current when updating from an invalid state
+///
+/// A watcher becoming valid never reads as a change
+fn update_seeded<T: Copy>(watcher: &mut Watcher<T>, value: Option<T>) {
+    if watcher.pair.is_none() {
+        watcher.update(value);
+    }
+    watcher.update(value);
 }
 
 /// MonoClass companion
@@ -59,28 +952,784 @@ struct StaticField<'a> {
 }
 
 impl StaticField<'_> {
+    fn path(&self) -> (Address, Vec<u64>) {
+        return (self.base_address, vec![0, self.field_offset]);
+    }
+
     fn read_value<T: CheckedBitPattern>(&self) -> Result<T, Error> {
         return self.process.read_pointer_path::<T>(self.base_address, PointerSize::Bit64, &[0, self.field_offset]);
     }
 }
 
+/// Field of a singleton class instance (`<instance>k__BackingField`)
+///
+/// The class is reloaded while invalid, and the field is located once the class is valid.
+/// Everything found is cached: field offsets never change for a loaded class, so only a class
+/// that is None (e.g. before it is loaded) is ever looked up again, together with its offsets and location.
+struct InstanceField<'a> {
+    class_name: &'static str,
+    /// Candidate field names across game versions, the first one found is used
+    field_names: &'static [&'static str],
+    class: Option<Class>,
+    /// Offsets of `<instance>k__BackingField` and of the value field
+    offsets: Option<(u64, u64)>,
+    loc: Option<StaticField<'a>>,
+}
+
+impl<'a> InstanceField<'a> {
+    fn new(class_name: &'static str, field_names: &'static [&'static str]) -> Self {
+        Self { class_name, field_names, class: None, offsets: None, loc: None }
+    }
+
+    /// Attempt to reload the class and locate the field when invalid, anything already found is kept
+    fn resolve(&mut self, process: &'a Process, monomod: &Module, image: &Image) {
+        if self.class.is_none() {
+            self.class = image.get_class(process, monomod, self.class_name);
+            self.offsets = None;
+            self.loc = None;
+        }
+
+        if let Some(class) = self.class.as_ref() {
+            if self.offsets.is_none() {
+                let instance_field = class.get_field_offset(process, monomod, "<instance>k__BackingField");
+                let value_field = self.field_names.iter().find_map(|&name| class.get_field_offset(process, monomod, name));
+                if let (Some(instance_field), Some(value_field)) = (instance_field, value_field) {
+                    self.offsets = Some((instance_field.into(), value_field.into()));
+                }
+            }
+            // the static table can be unallocated for a while after the class has loaded
+            if let (None, Some((instance_field, value_field))) = (self.loc.as_ref(), self.offsets) {
+                if let Some(static_table) = class.get_static_table(process, monomod) {
+                    self.loc = Some(StaticField{process, base_address: static_table.add(instance_field), field_offset: value_field});
+                }
+            }
+        }
+    }
+
+    /// None while the field is unresolved or the instance is invalid
+    fn read<T: CheckedBitPattern>(&self) -> Option<T> {
+        return self.loc.as_ref()?.read_value::<T>().ok();
+    }
+
+    /// None while unresolved
+    fn path(&self) -> Option<(Address, Vec<u64>)> {
+        return self.loc.as_ref().map(StaticField::path);
+    }
+
+    /// The class was found in the image
+    fn class_loaded(&self) -> bool {
+        return self.class.is_some();
+    }
+
+    /// Address of the current instance, None while unresolved or null
+    fn instance(&self) -> Option<Address> {
+        let loc = self.loc.as_ref()?;
+        return loc.process.read_pointer(loc.base_address, PointerSize::Bit64).ok().filter(|instance| !instance.is_null());
+    }
+}
+
+/// Pointer path that crosses several classes, e.g. `NetworkUser.localPlayers[0].netLunarCoins`
+///
+/// `resolver` returns the static base address and the pointer path once every class and field has loaded
+struct FieldPath {
+    resolver: fn(&Process, &Module, &Image) -> Option<(Address, Vec<u64>)>,
+    loc: Option<(Address, Vec<u64>)>,
+}
+
+impl FieldPath {
+    fn new(resolver: fn(&Process, &Module, &Image) -> Option<(Address, Vec<u64>)>) -> Self {
+        Self { resolver, loc: None }
+    }
+
+    /// Attempt to locate the path when unresolved
+    fn resolve(&mut self, process: &Process, monomod: &Module, image: &Image) {
+        if self.loc.is_none() {
+            self.loc = (self.resolver)(process, monomod, image);
+        }
+    }
+
+    /// None while unresolved
+    fn path(&self) -> Option<(Address, Vec<u64>)> {
+        return self.loc.clone();
+    }
+
+    /// None while the path is unresolved or any object along the path is invalid
+    fn read<T: CheckedBitPattern>(&self, process: &Process) -> Option<T> {
+        let (base_address, path) = self.loc.as_ref()?;
+        return process.read_pointer_path::<T>(*base_address, PointerSize::Bit64, path).ok();
+    }
+}
+
+/// Offset of a class field, trying each candidate name in order
+fn find_field(process: &Process, monomod: &Module, image: &Image, class_name: &str, field_names: &[&str]) -> Option<u64> {
+    let class = image.get_class(process, monomod, class_name)?;
+    return field_names.iter().find_map(|&name| class.get_field_offset(process, monomod, name)).map(u64::from);
+}
+
+/// Address of a static class field, trying each candidate name in order
+fn find_static_field(process: &Process, monomod: &Module, image: &Image, class_name: &str, field_names: &[&str]) -> Option<Address> {
+    let class = image.get_class(process, monomod, class_name)?;
+    let offset = field_names.iter().find_map(|&name| class.get_field_offset(process, monomod, name))?;
+    let static_table = class.get_static_table(process, monomod)?;
+    return Some(static_table.add(offset.into()));
+}
+
+/// NetworkUser.localPlayers[0].bodyIndexPreference
+///
+/// BodyIndex.None (-1) until a survivor is picked
+fn local_body_preference_path(process: &Process, monomod: &Module, image: &Image) -> Option<(Address, Vec<u64>)> {
+    let local_players = find_static_field(process, monomod, image, "NetworkUser", &["localPlayers"])?;
+    let body_preference = find_field(process, monomod, image, "NetworkUser", &["_bodyIndexPreference", "bodyIndexPreference"])?;
+    return Some((local_players, vec![0, LIST_ITEMS_OFFSET, ARRAY_DATA_OFFSET, body_preference]));
+}
+
+/// BodyCatalog.bodyNames, indexed by BodyIndex
+fn body_names_path(process: &Process, monomod: &Module, image: &Image) -> Option<(Address, Vec<u64>)> {
+    let body_names = find_static_field(process, monomod, image, "BodyCatalog", &["bodyNames"])?;
+    return Some((body_names, vec![0]));
+}
+
+/// Body name at the body index, None while either is invalid or the name does not fit
+fn read_body_name(process: &Process, body_names_loc: &FieldPath, body_index_loc: &FieldPath) -> Option<ArrayString<32>> {
+    let body_index = body_index_loc.read::<i32>(process).filter(|&body_index| body_index >= 0)? as u64;
+    let body_names = body_names_loc.read::<u64>(process).filter(|&body_names| body_names != 0)?;
+    let body_count = process.read::<u64>(Address::new(body_names + ARRAY_LENGTH_OFFSET)).ok()?;
+    if body_index >= body_count {
+        return None;
+    }
+    let name = process.read::<u64>(Address::new(body_names + ARRAY_DATA_OFFSET + 8 * body_index)).ok().filter(|&name| name != 0)?;
+    let len = process.read::<i32>(Address::new(name + STRING_LENGTH_OFFSET)).ok().filter(|&len| (1..=32).contains(&len))? as usize;
+    let mut buf = [0u8; 64];
+    process.read_into_buf(Address::new(name + STRING_CHARS_OFFSET), &mut buf[..2 * len]).ok()?;
+    let utf16: Vec<u16> = buf[..2 * len].chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+    return ArrayString::<32>::from(&String::from_utf16(&utf16).ok()?).ok();
+}
+
+/// NetworkUser.localPlayers[0].netLunarCoins
+fn local_lunar_coins_path(process: &Process, monomod: &Module, image: &Image) -> Option<(Address, Vec<u64>)> {
+    let local_players = find_static_field(process, monomod, image, "NetworkUser", &["localPlayers"])?;
+    let lunar_coins = find_field(process, monomod, image, "NetworkUser", &["netLunarCoins"])?;
+    return Some((local_players, vec![0, LIST_ITEMS_OFFSET, ARRAY_DATA_OFFSET, lunar_coins]));
+}
+
+/// LocalUserManager.localUsersList[0].cachedMaster.lostBodyToDeath
+///
+/// Unlike PlayerCharacterMasterController.instances, this is always the local player in multiplayer
+fn local_player_dead_path(process: &Process, monomod: &Module, image: &Image) -> Option<(Address, Vec<u64>)> {
+    let local_users = find_static_field(process, monomod, image, "LocalUserManager", &["localUsersList"])?;
+    let master = find_field(process, monomod, image, "LocalUser", &["<cachedMaster>k__BackingField", "cachedMaster"])?;
+    let lost_body = find_field(process, monomod, image, "CharacterMaster", &["lostBodyToDeath", "<lostBodyToDeath>k__BackingField"])?;
+    return Some((local_users, vec![0, LIST_ITEMS_OFFSET, ARRAY_DATA_OFFSET, master, lost_body]));
+}
+
+/// LocalUserManager.localUsersList[0].cachedMaster.money
+fn local_gold_path(process: &Process, monomod: &Module, image: &Image) -> Option<(Address, Vec<u64>)> {
+    let local_users = find_static_field(process, monomod, image, "LocalUserManager", &["localUsersList"])?;
+    let master = find_field(process, monomod, image, "LocalUser", &["<cachedMaster>k__BackingField", "cachedMaster"])?;
+    let money = find_field(process, monomod, image, "CharacterMaster", &["_money", "money"])?;
+    return Some((local_users, vec![0, LIST_ITEMS_OFFSET, ARRAY_DATA_OFFSET, master, money]));
+}
+
+/// PlayerCharacterMasterController.instances.Count
+fn player_count_path(process: &Process, monomod: &Module, image: &Image) -> Option<(Address, Vec<u64>)> {
+    let instances = find_static_field(process, monomod, image, "PlayerCharacterMasterController", &["_instances"])?;
+    return Some((instances, vec![0, LIST_SIZE_OFFSET]));
+}
+
+/// PlayerCharacterMasterController.instances[0].body.equipmentSlot.stock
+fn local_equipment_stock_path(process: &Process, monomod: &Module, image: &Image) -> Option<(Address, Vec<u64>)> {
+    let instances = find_static_field(process, monomod, image, "PlayerCharacterMasterController", &["_instances"])?;
+    let body = find_field(process, monomod, image, "PlayerCharacterMasterController", &["body"])?;
+    let equipment_slot = find_field(process, monomod, image, "CharacterBody", &["<equipmentSlot>k__BackingField"])?;
+    let stock = find_field(process, monomod, image, "EquipmentSlot", &["<stock>k__BackingField"])?;
+    return Some((instances, vec![0, LIST_ITEMS_OFFSET, ARRAY_DATA_OFFSET, body, equipment_slot, stock]));
+}
+
+/// PlayerCharacterMasterController.instances[0].master.inventory.itemStacks
+///
+/// Item counts of the first local player, indexed by ItemIndex
+fn local_item_stacks_path(process: &Process, monomod: &Module, image: &Image) -> Option<(Address, Vec<u64>)> {
+    let instances = find_static_field(process, monomod, image, "PlayerCharacterMasterController", &["_instances"])?;
+    let master = find_field(process, monomod, image, "PlayerCharacterMasterController", &["<master>k__BackingField", "_master", "master"])?;
+    let inventory = find_field(process, monomod, image, "CharacterMaster", &["<inventory>k__BackingField", "inventory"])?;
+    let item_stacks = find_field(process, monomod, image, "Inventory", &["itemStacks"])?;
+    return Some((instances, vec![0, LIST_ITEMS_OFFSET, ARRAY_DATA_OFFSET, master, inventory, item_stacks]));
+}
+
+/// RoR2Content.Items.<TRACKED_ITEMS[ITEM]>.itemIndex
+///
+/// Item indices are assigned when the item catalog is initialized, invalid (-1) before that
+fn item_index_path<const ITEM: usize>(process: &Process, monomod: &Module, image: &Image) -> Option<(Address, Vec<u64>)> {
+    let item_def = find_static_field(process, monomod, image, "Items", &[TRACKED_ITEMS[ITEM]])?;
+    let item_index = find_field(process, monomod, image, "ItemDef", &["_itemIndex", "<itemIndex>k__BackingField"])?;
+    return Some((item_def, vec![0, item_index]));
+}
+
+/// Stacks of the item held, None while the inventory or the item index is invalid
+fn item_count(process: &Process, item_stacks_loc: &FieldPath, item_index_loc: &FieldPath) -> Option<i32> {
+    let item_stacks = item_stacks_loc.read::<u64>(process).filter(|&item_stacks| item_stacks != 0)?;
+    let item_index = item_index_loc.read::<i32>(process).filter(|&item_index| item_index >= 0)?;
+    return process.read::<i32>(Address::new(item_stacks + ARRAY_DATA_OFFSET + 4 * item_index as u64)).ok();
+}
+
+/// ItemCatalog.tier3ItemList, the item indices of every red item
+fn red_item_list_path(process: &Process, monomod: &Module, image: &Image) -> Option<(Address, Vec<u64>)> {
+    let item_list = find_static_field(process, monomod, image, "ItemCatalog", &["tier3ItemList"])?;
+    return Some((item_list, vec![0]));
+}
+
+/// Total stacks of every item in a List<ItemIndex>, None while the inventory or the list is invalid
+fn count_items_in_list(process: &Process, item_stacks_loc: &FieldPath, item_list_loc: &FieldPath) -> Option<i32> {
+    let item_stacks = item_stacks_loc.read::<u64>(process).filter(|&item_stacks| item_stacks != 0)?;
+    let item_list = item_list_loc.read::<u64>(process).filter(|&item_list| item_list != 0)?;
+    let items = process.read::<u64>(Address::new(item_list + LIST_ITEMS_OFFSET)).ok().filter(|&items| items != 0)?;
+    let size = process.read::<i32>(Address::new(item_list + LIST_SIZE_OFFSET)).ok()?;
+    let mut count = 0;
+    for i in 0..size.max(0) as u64 {
+        let item_index = process.read::<i32>(Address::new(items + ARRAY_DATA_OFFSET + 4 * i)).ok().filter(|&item_index| item_index >= 0)?;
+        count += process.read::<i32>(Address::new(item_stacks + ARRAY_DATA_OFFSET + 4 * item_index as u64)).ok()?;
+    }
+    return Some(count);
+}
+
+/// The item count went up from 0 for the first time this run
+///
+/// `held` remembers whether the item was held earlier in the run, it is cleared outside of a run
+fn first_pickup(count: &Watcher<i32>, in_run: bool, held: &mut bool) -> bool {
+    if !in_run {
+        *held = false;
+        return false;
+    }
+    if let Some(count) = count.pair {
+        if count.current > 0 {
+            let first = count.old == 0 && !*held;
+            *held = true;
+            return first;
+        }
+    }
+    return false;
+}
+
+/// Whether any of the items is held, None while the inventory or any item index is invalid
+fn holds_any_item(process: &Process, item_stacks_loc: &FieldPath, item_index_locs: &[FieldPath]) -> Option<bool> {
+    return any_item_held(item_index_locs.iter().map(|item_index_loc| item_count(process, item_stacks_loc, item_index_loc)));
+}
+
+/// Whether any of the item counts is above 0, None once a count is unavailable
+fn any_item_held(counts: impl IntoIterator<Item = Option<i32>>) -> Option<bool> {
+    let mut held = false;
+    for count in counts {
+        held |= count? > 0;
+    }
+    return Some(held);
+}
+
+/// Run.instance.waveIndex, only meaningful when the run is an InfiniteTowerRun (Simulacrum)
+fn simulacrum_wave_path(process: &Process, monomod: &Module, image: &Image) -> Option<(Address, Vec<u64>)> {
+    let instance = find_static_field(process, monomod, image, "Run", &["<instance>k__BackingField"])?;
+    let wave_index = find_field(process, monomod, image, "InfiniteTowerRun", &["_waveIndex", "<waveIndex>k__BackingField"])?;
+    return Some((instance, vec![0, wave_index]));
+}
+
+/// TeleporterInteraction.instance.holdoutZoneController.isAnyoneCharging
+fn teleporter_charging_path(process: &Process, monomod: &Module, image: &Image) -> Option<(Address, Vec<u64>)> {
+    let instance = find_static_field(process, monomod, image, "TeleporterInteraction", &["<instance>k__BackingField"])?;
+    let holdout_zone = find_field(process, monomod, image, "TeleporterInteraction", &["<holdoutZoneController>k__BackingField", "holdoutZoneController"])?;
+    let charging = find_field(process, monomod, image, "HoldoutZoneController", &["isAnyoneCharging"])?;
+    return Some((instance, vec![0, holdout_zone, charging]));
+}
+
+/// Run category, detected from the current scene
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Classic,
+    /// Simulacrum stages are prefixed with "it" (e.g. "itgolemplains")
+    Simulacrum,
+}
+
+/// Split toggles after applying category presets on top of user settings
+///
+/// Precedence: a toggle enabled in the user settings is always enabled,
+/// a preset can only enable toggles the user left disabled.
+struct EffectiveSettings {
+    ror2_stages: bool,
+    any_boss_splits: bool,
+}
+
 pub struct Game {
     pub settings: GameSettings,
     pub game_state: GameVars,
+    diagnostics: game::Diagnostics,
 }
 
-impl Game {
-    pub fn new() -> Self { Self { settings: GameSettings::register(), game_state: GameVars::default() } }
+impl Game {
+    pub fn new() -> Self { Self { settings: GameSettings::register(), game_state: GameVars::default(), diagnostics: game::Diagnostics::default() } }
+
+    fn reset_state(&mut self) {
+        self.game_state = GameVars::default();
+    }
+
+    /// First wave starting in Simulacrum, otherwise a regular Stage 1 fading in
+    fn run_started(&self) -> bool {
+        if self.detected_category() == Some(Category::Simulacrum) {
+            if let Some(simulacrum_wave) = self.game_state.simulacrum_wave.pair {
+                return simulacrum_wave.changed_from_to(&0, &1);
+            }
+            return false;
+        }
+        if self.settings.ror2_start_on_run && self.game_state.run_created {
+            if let Some(scene) = self.game_state.scene.pair {
+                return scene_kind(&scene.current) != Some(SceneKind::Menu);
+            }
+        }
+        return self.stage_one_fade_in();
+    }
+
+    /// Loading state from the direction of the fade, None when undetermined
+    fn fade_loading(&self) -> Option<bool> {
+        if let Some(fade) = self.game_state.fade.pair {
+            if fade.increased() {
+                return Some(true);
+            }
+            if fade.decreased() && fade.current > 0.0 || fade.current == 0.0 {
+                return Some(false);
+            }
+        }
+        // maintain previous state when fade in/out is undetermined (aka current == previous)
+        return None;
+    }
+
+    /// Updates the fade, its crossing below 1.0, and how long the loading state stayed undetermined
+    fn update_fade(&mut self, fade: Option<f32>) {
+        update_seeded(&mut self.game_state.fade, fade);
+
+        // the fade crossing below 1.0, tolerating a few missed reads around the crossing
+        self.game_state.fade_in_crossed = false;
+        match self.game_state.fade.pair {
+            Some(fade) if fade.current >= 1.0 => { self.game_state.ticks_since_opaque = Some(0); },
+            Some(_) => {
+                let tolerance = self.settings.fade_start_tolerance.ticks();
+                self.game_state.fade_in_crossed = self.game_state.ticks_since_opaque.is_some_and(|ticks| ticks <= tolerance);
+                self.game_state.ticks_since_opaque = None;
+            },
+            None => { self.game_state.ticks_since_opaque = self.game_state.ticks_since_opaque.map(|ticks| ticks + 1); },
+        }
+
+        if self.fade_loading().is_none() {
+            self.game_state.undetermined_fade_ticks += 1;
+        } else {
+            self.game_state.undetermined_fade_ticks = 0;
+        }
+    }
+
+    /// Updates the Run instance, stage count, and results screen, and whether the run was just created or has ended
+    fn update_run(&mut self, run_instance: Option<Address>, stage_count: Option<i32>, results: Option<bool>) {
+        // a new Run instance may briefly hold a stale count, start over so only increments within this run split
+        self.game_state.run_instance.update(run_instance);
+        if self.game_state.run_instance.pair.is_some_and(|run_instance| run_instance.changed()) {
+            self.game_state.stage_count = Watcher::default();
+        }
+        let had_run = self.game_state.stage_count.pair.is_some();
+        update_seeded(&mut self.game_state.stage_count, stage_count);
+        // the lobby has no Run instance, so the count only seeds at 0 once the run itself exists
+        self.game_state.run_created = !had_run && self.game_state.stage_count.pair.is_some_and(|stage_count| stage_count.current == 0);
+
+        update_seeded(&mut self.game_state.results, results);
+        if self.game_state.results.pair.is_some_and(|results| results.current) {
+            self.game_state.run_ended = true;
+        }
+    }
+
+    /// Updates what follows from this tick's scene: seen stages, loops, Voidling phases, and leaving an ended run
+    fn update_scene_progress(&mut self) {
+        self.update_seen_stages();
+        if self.game_state.stage_count.pair.is_none() {
+            self.game_state.loop_count = 0;
+        } else if self.looped() {
+            self.game_state.loop_count += 1;
+        }
+        match self.game_state.scene.pair {
+            Some(scene) if scene.current.as_str() == "voidraid" => {
+                if self.boss_killed() {
+                    self.game_state.voidling_phases_cleared += 1;
+                }
+            },
+            _ => { self.game_state.voidling_phases_cleared = 0; }
+        }
+        if self.game_state.run_ended && self.run_started() {
+            self.game_state.run_ended = false;
+        }
+    }
+
+    /// Counts teleporters charged this run
+    fn update_teleporter_count(&mut self) {
+        if self.game_state.stage_count.pair.is_none() {
+            self.game_state.teleporter_count = 0;
+        } else if self.game_state.teleporter_charged.pair.is_some_and(|charged| charged.changed_from_to(&false, &true)) {
+            self.game_state.teleporter_count += 1;
+        }
+    }
+
+    /// Tracks the gold milestones reached, each milestone only counts once per run
+    fn update_gold_milestones(&mut self) {
+        if self.game_state.stage_count.pair.is_none() || self.game_state.gold_milestone_choice != Some(self.settings.split_on_gold) {
+            self.game_state.gold_milestones = 0;
+            self.game_state.gold_milestone_choice = Some(self.settings.split_on_gold);
+        }
+        self.game_state.gold_milestone_reached = false;
+        if let (Some(step), Some(gold)) = (self.settings.split_on_gold.step(), self.game_state.gold.pair) {
+            let milestones = gold.current / step;
+            if milestones > self.game_state.gold_milestones {
+                // gold already held when the watcher becomes valid is not a new milestone
+                self.game_state.gold_milestone_reached = gold.increased();
+                self.game_state.gold_milestones = milestones;
+            }
+        }
+    }
+
+    /// Counts purchases during the current Bazaar visit, starting over on every visit
+    fn update_bazaar_purchases(&mut self) {
+        if let Some(scene) = self.game_state.scene.pair {
+            if scene.changed() && scene.current.as_str() == "bazaar" {
+                self.game_state.bazaar_purchases = 0;
+            }
+        }
+        if self.bazaar_purchase() {
+            self.game_state.bazaar_purchases += 1;
+        }
+    }
+
+    /// Tracks stages seen this run, a stage is a repeat from the tick it is entered
+    fn update_seen_stages(&mut self) {
+        if self.game_state.stage_count.pair.is_none() {
+            self.game_state.seen_stages.clear();
+            self.game_state.repeat_stage = false;
+            return;
+        }
+        let Some(scene) = self.game_state.scene.pair else { return; };
+        let Ok(stage) = ArrayString::<SCENE_NAME_LEN>::from(stage_display_name(&scene.current)) else { return; };
+        let seen = self.game_state.seen_stages.contains(&stage);
+        if scene.changed() {
+            self.game_state.repeat_stage = seen;
+        }
+        if !seen {
+            self.game_state.seen_stages.push(stage);
+        }
+    }
+
+    /// Regular Stage 1 fading in
+    ///
+    /// Fires once, on the first fade read below 1.0 after the fade was at or above 1.0
+    fn stage_one_fade_in(&self) -> bool {
+        if let Some(scene) = self.game_state.scene.pair {
+            if is_stage_one(&scene.current) {
+                return self.game_state.fade_in_crossed;
+            }
+        }
+        return false;
+    }
+
+    /// Entered Commencement or The Planetarium, or entered or left a special scene
+    fn special_scene_split(&self) -> bool {
+        if let Some(scene) = self.game_state.scene.pair {
+            // reached Commencement, stage transitions never split on Commencement themselves
+            if self.settings.commencement_enter_split {
+                if scene.changed() && scene.current.starts_with("moon") && !scene.old.starts_with("moon") {
+                    return true;
+                }
+            }
+            // reached The Planetarium
+            if self.settings.voidraid_enter_split && scene.changed() && scene.current.as_str() == "voidraid" {
+                return true;
+            }
+            // entered or left a special scene
+            if scene.changed() {
+                let (enter, _) = self.special_scene_toggles(&scene.current);
+                let (_, exit) = self.special_scene_toggles(&scene.old);
+                return enter || exit;
+            }
+        }
+        return false;
+    }
+
+    /// Enter and exit split toggles of a special scene, both false for every other scene
+    fn special_scene_toggles(&self, scene: &str) -> (bool, bool) {
+        return match scene {
+            "bazaar" => (self.settings.bazaar_enter, self.settings.bazaar_exit),
+            "arena" => (self.settings.arena_enter, self.settings.arena_exit),
+            "goldshores" => (self.settings.goldshores_enter, self.settings.goldshores_exit),
+            "artifactworld" => (self.settings.artifactworld_enter, self.settings.artifactworld_exit),
+            _ => (false, false)
+        };
+    }
+
+    /// Entered a Stage 1 after clearing stages this run
+    ///
+    /// stageClearCount also counts Commencement, so loops are detected by the scene rather than by the count
+    fn looped(&self) -> bool {
+        if let (Some(scene), Some(stage_count)) = (self.game_state.scene.pair, self.game_state.stage_count.pair) {
+            return scene.changed() && is_stage_one(&scene.current) && stage_count.current > 0;
+        }
+        return false;
+    }
+
+    /// Looped back to a Stage 1, the stage transition split already covers this
+    fn loop_split(&self) -> bool {
+        return self.settings.ror2_loop_splits && !self.effective_settings().ror2_stages && self.looped();
+    }
+
+    /// Stage count increased, or entered a stage with its individual split enabled
+    ///
+    /// `ror2_stages` covers every stage, including modded stages with unknown scene names or an unreadable scene.
+    /// Individual stage splits only apply while it is disabled, and only to known stages.
+    /// Stages seen earlier in the run never split when only unique stages split
+    fn stage_split(&self) -> bool {
+        if self.settings.unique_stage_splits && self.game_state.repeat_stage {
+            return false;
+        }
+        if !self.effective_settings().ror2_stages {
+            if let (Some(scene), Some(_)) = (self.game_state.scene.pair, self.game_state.stage_count.pair) {
+                return scene.changed() && self.individual_stage_split(&scene.current);
+            }
+            return false;
+        }
+        if let Some(stage_count) = self.game_state.stage_count.pair {
+            if stage_count.current >= 1 && stage_count.increased() {
+                // avoid double splits on Commencement
+                return match self.game_state.scene.pair {
+                    Some(scene) => !scene.current.starts_with("moon"),
+                    _ => true
+                }
+            }
+        }
+        return false;
+    }
+
+    /// Individual split toggle of a regular stage, variants share the toggle of their stage
+    fn individual_stage_split(&self, scene: &str) -> bool {
+        let toggles = [
+            ("golemplains", self.settings.split_golemplains),
+            ("blackbeach", self.settings.split_blackbeach),
+            ("snowyforest", self.settings.split_snowyforest),
+            ("lakes", self.settings.split_lakes),
+            ("village", self.settings.split_village),
+            ("goolake", self.settings.split_goolake),
+            ("foggyswamp", self.settings.split_foggyswamp),
+            ("ancientloft", self.settings.split_ancientloft),
+            ("lemuriantemple", self.settings.split_lemuriantemple),
+            ("frozenwall", self.settings.split_frozenwall),
+            ("wispgraveyard", self.settings.split_wispgraveyard),
+            ("sulfurpools", self.settings.split_sulfurpools),
+            ("habitat", self.settings.split_habitat),
+            ("dampcavesimple", self.settings.split_dampcavesimple),
+            ("shipgraveyard", self.settings.split_shipgraveyard),
+            ("rootjungle", self.settings.split_rootjungle),
+            ("skymeadow", self.settings.split_skymeadow),
+            ("helminthroost", self.settings.split_helminthroost),
+            ("meridian", self.settings.split_meridian),
+        ];
+        return toggles.iter().find(|(prefix, _)| scene.starts_with(prefix)).is_some_and(|&(_, enabled)| enabled);
+    }
+
+    /// Events during a stage
+    fn event_split(&self) -> bool {
+        // charged a teleporter, the watcher is invalid on stages without a teleporter
+        if self.settings.ror2_teleporter {
+            if let Some(teleporter_charged) = self.game_state.teleporter_charged.pair {
+                if teleporter_charged.changed_from_to(&false, &true) {
+                    return true;
+                }
+            }
+        }
+        // reached the target number of charged teleporters
+        if let (Some(target), Some(teleporter_charged)) = (self.settings.split_on_teleporter_count.target(), self.game_state.teleporter_charged.pair) {
+            if teleporter_charged.changed_from_to(&false, &true) && self.game_state.teleporter_count == target {
+                return true;
+            }
+        }
+        // reached a gold milestone
+        if self.game_state.gold_milestone_reached {
+            return true;
+        }
+        // teleporter charge paused
+        if self.settings.teleporter_pause_splits {
+            if let Some(teleporter_charging) = self.game_state.teleporter_charging.pair {
+                if teleporter_charging.changed_from_to(&true, &false) {
+                    return true;
+                }
+            }
+        }
+        // activated a Shrine of the Mountain
+        // a new stage starts the count over from a new TeleporterInteraction, which never reads as an increase
+        if self.settings.ror2_mountain_shrines {
+            if let Some(mountain_shrines) = self.game_state.mountain_shrines.pair {
+                if mountain_shrines.increased() {
+                    return true;
+                }
+            }
+        }
+        // started a Mithrix phase
+        // entering Commencement is a scene change, the phase never increments on that tick
+        if self.settings.ror2_mithrix_phases {
+            if let Some(mithrix_phase) = self.game_state.mithrix_phase.pair {
+                if mithrix_phase.increased() {
+                    return true;
+                }
+            }
+        }
+        // completed a Simulacrum wave, the first wave starting is the start instead
+        if self.settings.ror2_simulacrum_waves {
+            if let Some(simulacrum_wave) = self.game_state.simulacrum_wave.pair {
+                if simulacrum_wave.increased() && simulacrum_wave.old >= 1 {
+                    return true;
+                }
+            }
+        }
+        // bought something in the Bazaar
+        if self.settings.bazaar_purchase_splits && self.bazaar_purchase() {
+            return true;
+        }
+        // used equipment
+        if self.settings.equipment_use_splits {
+            if let Some(equipment_stock) = self.game_state.equipment_stock.pair {
+                if equipment_stock.decreased() {
+                    return true;
+                }
+            }
+        }
+        // picked up the route item for the first time this run
+        if self.settings.split_on_item && self.game_state.route_item_acquired {
+            return true;
+        }
+        // picked up the first red item this run
+        if self.settings.ror2_first_red && self.game_state.first_red_acquired {
+            return true;
+        }
+        // captured a cell or cleared Void Fields
+        if let Some(arena_cleared_rounds) = self.game_state.arena_cleared_rounds.pair {
+            if arena_cleared_rounds.increased() {
+                if self.settings.ror2_arena_cells || self.settings.void_fields_complete_split && arena_cleared_rounds.current == ARENA_ROUNDS {
+                    return true;
+                }
+            }
+        }
+        // completed the Artifact Trial
+        if self.settings.artifactworld_on_success {
+            if let Some(artifact_trial_complete) = self.game_state.artifact_trial_complete.pair {
+                if artifact_trial_complete.changed_from_to(&false, &true) {
+                    return true;
+                }
+            }
+        }
+        // started the obliteration
+        if self.settings.obliterate_initiate_split && self.obliteration_initiated() {
+            return true;
+        }
+        // killed a boss
+        if self.effective_settings().any_boss_splits && self.boss_killed() {
+            return true;
+        }
+        // killed Voidling
+        if self.settings.ror2_voidling_kill == VoidlingKill::Split && self.voidling_killed() {
+            return true;
+        }
+        return false;
+    }
+
+    /// A boss group was killed during gameplay
+    ///
+    /// Boss groups are also cleaned up when leaving a stage, so only count them while the scene is fully faded in
+    fn boss_killed(&self) -> bool {
+        if let (Some(boss_groups), Some(scene), Some(fade)) = (self.game_state.boss_groups.pair, self.game_state.scene.pair, self.game_state.fade.pair) {
+            return boss_groups.decreased() && !scene.changed() && fade.current == 0.0;
+        }
+        return false;
+    }
+
+    /// The final Voidling phase was cleared this tick
+    ///
+    /// Only counted in The Planetarium, so obliterating in A Moment, Fractured never counts
+    fn voidling_killed(&self) -> bool {
+        return self.game_state.voidling_phases_cleared == VOIDLING_PHASES && self.boss_killed()
+            && self.game_state.scene.pair.is_some_and(|scene| scene.current.as_str() == "voidraid");
+    }
+
+    /// Frame clock minus fixed clock in milliseconds, None outside of a run
+    fn run_time_drift_ms(&self) -> Option<i32> {
+        let (Some(run_time), Some(fixed_time)) = (self.game_state.run_time.pair, self.game_state.run_fixed_time.pair) else { return None; };
+        return Some(((run_time.current - fixed_time.current) * 1000.0).round() as i32);
+    }
+
+    /// The Obelisk can be used: A Moment, Fractured is fully faded in and the run has not ended
+    ///
+    /// None outside of A Moment, Fractured
+    fn obelisk_available(&self) -> Option<bool> {
+        let scene = self.game_state.scene.pair.filter(|scene| scene.current.as_str() == "mysteryspace")?;
+        let fade = self.game_state.fade.pair?;
+        let run_ended = self.game_state.results.pair.is_some_and(|results| results.current);
+        return Some(!scene.changed() && fade.current == 0.0 && !run_ended);
+    }
+
+    /// The screen started fading out in A Moment, Fractured
+    ///
+    /// The Obelisk is the only way out of the scene, so the only fade out is the obliteration
+    fn obliteration_initiated(&self) -> bool {
+        if let (Some(scene), Some(fade)) = (self.game_state.scene.pair, self.game_state.fade.pair) {
+            return scene.current.as_str() == "mysteryspace" && !scene.changed() && fade.old == 0.0 && fade.increased();
+        }
+        return false;
+    }
+
+    /// The local player is alive at the end of a multiplayer run
+    ///
+    /// In single-player, or when either value is unavailable, the results screen alone decides
+    fn local_player_reached_ending(&self) -> bool {
+        if let (Some(player_count), Some(local_player_dead)) = (self.game_state.player_count.pair, self.game_state.local_player_dead.pair) {
+            return player_count.current <= 1 || !local_player_dead.current;
+        }
+        return true;
+    }
+
+    /// Lunar coins were spent in the Bazaar
+    fn bazaar_purchase(&self) -> bool {
+        if let (Some(scene), Some(lunar_coins)) = (self.game_state.scene.pair, self.game_state.lunar_coins.pair) {
+            return scene.current.as_str() == "bazaar" && !scene.changed() && lunar_coins.decreased();
+        }
+        return false;
+    }
+
+    /// No Heresy item is held, None outside of a run
+    fn legal(&self) -> Option<bool> {
+        return self.game_state.holds_heresy_item.map(|held| !held);
+    }
 
-    fn reset_state(&mut self) {
-        self.game_state = GameVars::default();
+    /// None outside of a run
+    fn detected_category(&self) -> Option<Category> {
+        self.game_state.stage_count.pair?;
+        let scene = self.game_state.scene.pair?;
+        if scene.current.starts_with("it") {
+            return Some(Category::Simulacrum);
+        }
+        return Some(Category::Classic);
+    }
+
+    fn effective_settings(&self) -> EffectiveSettings {
+        let preset = if self.settings.category_presets { self.detected_category() } else { None };
+        return EffectiveSettings {
+            ror2_stages: self.settings.ror2_stages || preset == Some(Category::Classic),
+            any_boss_splits: self.settings.any_boss_splits || preset == Some(Category::Simulacrum),
+        };
     }
 }
 
 #[async_trait]
 impl game::GameAutoSplitter for Game {
+    fn display_name(&self) -> &'static str { return INFO.display_name; }
+
+    fn id(&self) -> game::GameId { return INFO.id; }
+
     /// "Risk of Rain 2.exe"
-    fn process_names(&self) -> &[&str] { return &[TARGET_PROCESS_NAME]; }
+    fn process_names(&self) -> &[&str] { return INFO.process_names; }
 
     fn register_settings(&mut self) {
         self.settings = GameSettings::register();
@@ -94,26 +1743,110 @@ impl game::GameAutoSplitter for Game {
 
         // Workaround for version detection: wait until the scene is valid
         // before attempting to load RoR2.dll/Assembly-CSharp.dll
-        // FIXME replace with file check + wait_get_image
-        retry(|| sceneman.get_current_scene_path::<256>(&process)).await;
+        retry(|| sceneman.get_current_scene_path::<SCENE_PATH_LEN>(&process)).await;
 
         // SotV onwards uses RoR2.dll, earlier versions use Assembly-CSharp.dll
-        // FIXME breaks version assumption if RoR2.dll has not yet loaded
-        // check if file "RoR2.dll" exists once wasi support is merged.
-        if let Some(ror2) = monomod.get_image(&process, "RoR2").or(monomod.get_default_image(&process)) {
+        // RoR2.dll can load a little after the scene is valid, so wait for it a while before assuming an older version.
+        // FIXME check if file "RoR2.dll" exists once wasi support is merged.
+        // The image is selected again below when none of the core classes can be found in it.
+        let mut image_wait_ticks : u32 = 0;
+        let image = loop {
+            if let Some(image) = monomod.get_image(&process, "RoR2") {
+                break Some((image, "SotV or later (RoR2.dll)"));
+            }
+            if image_wait_ticks as f64 >= IMAGE_WAIT_SECONDS * TICK_RATE {
+                break monomod.get_default_image(&process).map(|image| (image, "before SotV (Assembly-CSharp.dll)"));
+            }
+            image_wait_ticks += 1;
+            next_tick().await;
+        };
+        if let Some((mut ror2, version)) = image {
+            if autosplitter.debug() {
+                asr::print_message(&format!("{}: using {}", INFO.display_name, version));
+            }
+            self.diagnostics = game::Diagnostics {
+                process_name: Some(TARGET_PROCESS_NAME),
+                module: process.get_module_range(TARGET_PROCESS_NAME).ok(),
+                version: Some(version),
+                paths: Vec::new(),
+            };
 
             // FadeToBlackManager exists almost at the start of the process, but starts off invalid
             let mut ftbm = ror2.get_class(&process, &monomod, "FadeToBlackManager");
-            // Run exists from entering the lobby onwards
-            let mut run = ror2.get_class(&process, &monomod, "Run");
-            // GameOverController exists just before the end of a run, including dying
-            let mut goc = ror2.get_class(&process, &monomod, "GameOverController");
             // alpha valid when FadeToBlackManager exists
             let mut alpha_loc : Option<Address> = None;
+            // Run exists from entering the lobby onwards
             // stageClearCount only valid during a run (not valid in the lobby)
-            let mut stage_loc : Option<StaticField> = None;
+            let mut stage_loc = InstanceField::new("Run", &["stageClearCount"]);
+            // GameOverController exists just before the end of a run, including dying
             // shouldDisplayGameEndReportPanels valid when GameOverController exists
-            let mut panel_loc : Option<StaticField> = None;
+            let mut panel_loc = InstanceField::new("GameOverController", &[
+                "<shouldDisplayGameEndReportPanels>k__BackingField",
+                "_shouldDisplayGameEndReportPanels", // versions after SotS (starting with manifest 4567638355138669926 on 2024-08-27)
+            ]);
+            // ArtifactTrialMissionController only exists in Bulwark's Ambry
+            let mut trial_loc = InstanceField::new("ArtifactTrialMissionController", &["<missionCompleted>k__BackingField", "missionCompleted"]);
+            // TeleporterInteraction only exists on stages with a teleporter
+            let mut teleporter_loc = InstanceField::new("TeleporterInteraction", &["activationStateInternal"]);
+            let mut mountain_loc = InstanceField::new("TeleporterInteraction", &["shrineBonusStacks", "_shrineBonusStacks"]);
+            // player bodies only exist during a run
+            let mut equipment_loc = FieldPath::new(local_equipment_stock_path);
+            let mut local_player_dead_loc = FieldPath::new(local_player_dead_path);
+            let mut player_count_loc = FieldPath::new(player_count_path);
+            let mut gold_loc = FieldPath::new(local_gold_path);
+            let mut teleporter_charging_loc = FieldPath::new(teleporter_charging_path);
+            let mut simulacrum_wave_loc = FieldPath::new(simulacrum_wave_path);
+            let mut item_stacks_loc = FieldPath::new(local_item_stacks_path);
+            let mut heresy_locs = [
+                FieldPath::new(item_index_path::<0>),
+                FieldPath::new(item_index_path::<1>),
+                FieldPath::new(item_index_path::<2>),
+                FieldPath::new(item_index_path::<3>),
+            ];
+            // filled once the item catalog is initialized
+            let mut red_item_list_loc = FieldPath::new(red_item_list_path);
+            let mut route_item_locs = [
+                FieldPath::new(item_index_path::<{FIRST_ROUTE_ITEM}>),
+                FieldPath::new(item_index_path::<{FIRST_ROUTE_ITEM + 1}>),
+                FieldPath::new(item_index_path::<{FIRST_ROUTE_ITEM + 2}>),
+                FieldPath::new(item_index_path::<{FIRST_ROUTE_ITEM + 3}>),
+            ];
+            // PhaseCounter only exists during the Mithrix fight
+            let mut phase_loc = InstanceField::new("PhaseCounter", &["<phase>k__BackingField", "phase"]);
+            // Run clocks, only valid during a run
+            let mut fixed_time_loc = InstanceField::new("Run", &["fixedTime"]);
+            let mut run_time_loc = InstanceField::new("Run", &["time"]);
+            // Run.RunStopwatch { float offsetFromFixedTime; bool isPaused; }
+            let mut stopwatch_loc = InstanceField::new("Run", &["runStopwatch", "_runStopwatch"]);
+            let mut difficulty_loc = InstanceField::new("Run", &["selectedDifficultyInternal"]);
+            // ArenaMissionController only exists in Void Fields
+            let mut arena_loc = InstanceField::new("ArenaMissionController", &["_clearedRounds", "clearedRounds"]);
+            // local players exist from the title screen onwards
+            let mut lunar_coins_loc = FieldPath::new(local_lunar_coins_path);
+            let mut body_preference_loc = FieldPath::new(local_body_preference_path);
+            // BodyCatalog is filled once the game has finished loading
+            let mut body_names_loc = FieldPath::new(body_names_path);
+            // BossGroup.instancesList is static, valid as soon as the class is loaded
+            let mut bossgroup = ror2.get_class(&process, &monomod, "BossGroup");
+            let mut boss_loc : Option<StaticField> = None;
+
+            let mut scene_var = game::TimerVariable::new("Scene");
+            let mut stage_var = game::TimerVariable::new("Stage");
+            let mut legal_var = game::TimerVariable::new("Legal");
+            let mut teleporter_charging_var = game::TimerVariable::new("Teleporter Charging");
+            let mut survivor_var = game::TimerVariable::new("Survivor");
+            let mut loop_var = game::TimerVariable::new("Loop");
+            let mut gold_var = game::TimerVariable::new("Gold");
+            let mut obelisk_var = game::TimerVariable::new("Obelisk Available");
+            let mut fixed_time_var = game::TimerVariable::new("Run Fixed Time");
+            let mut run_time_var = game::TimerVariable::new("Run Time");
+            let mut drift_var = game::TimerVariable::new("Run Time Drift (ms)");
+
+            // last scene name too long to store, so it is only reported once
+            let mut long_scene : Option<String> = None;
+
+            // ticks in a valid scene without FadeToBlackManager or Run in the selected image
+            let mut missing_class_ticks : u32 = 0;
 
             loop {
                 // attmept to reload class fields when invalid
@@ -122,16 +1855,6 @@ impl game::GameAutoSplitter for Game {
                     alpha_loc = None;
                 }
 
-                if run.is_none() {
-                    run = ror2.get_class(&process, &monomod, "Run");
-                    stage_loc = None;
-                }
-
-                if goc.is_none() {
-                    goc = ror2.get_class(&process, &monomod, "GameOverController");
-                    panel_loc = None;
-                }
-
                 if let Some(ftbm) = ftbm.as_ref() {
                     if alpha_loc.is_none() {
                         let alpha_offset = ftbm.get_field_offset(&process, &monomod, "alpha");
@@ -142,65 +1865,205 @@ impl game::GameAutoSplitter for Game {
                     }
                 }
 
-                if let Some(run) = run.as_ref() {
-                    if stage_loc.is_none() {
-                        let instance_field = run.get_field_offset(&process, &monomod, "<instance>k__BackingField");
-                        let scc_field = run.get_field_offset(&process, &monomod, "stageClearCount");
-                        let static_table = run.get_static_table(&process, &monomod);
-                        if let (Some(instance_field), Some(static_table), Some(scc_field)) = (instance_field, static_table, scc_field) {
-                            let instance_addr = static_table.add(instance_field.into());
-                            stage_loc = Some(StaticField{process: &process, base_address: instance_addr, field_offset: scc_field.into()})
+                if bossgroup.is_none() {
+                    bossgroup = ror2.get_class(&process, &monomod, "BossGroup");
+                    boss_loc = None;
+                }
+
+                if let Some(bossgroup) = bossgroup.as_ref() {
+                    if boss_loc.is_none() {
+                        let list_offset = bossgroup.get_field_offset(&process, &monomod, "instancesList");
+                        let static_table = bossgroup.get_static_table(&process, &monomod);
+                        if let (Some(list_offset), Some(static_table)) = (list_offset, static_table) {
+                            boss_loc = Some(StaticField{process: &process, base_address: static_table.add(list_offset.into()), field_offset: LIST_SIZE_OFFSET});
                         }
                     }
                 }
 
-                if let Some(goc) = goc.as_ref() {
-                    if panel_loc.is_none() {
-                        let instance_field = goc.get_field_offset(&process, &monomod, "<instance>k__BackingField");
-                        let sdgerp_field = goc.get_field_offset(&process, &monomod, "<shouldDisplayGameEndReportPanels>k__BackingField")
-                            .or_else(|| goc.get_field_offset(&process, &monomod, "_shouldDisplayGameEndReportPanels")); // versions after SotS (starting with manifest 4567638355138669926 on 2024-08-27)
-                        let static_table = goc.get_static_table(&process, &monomod);
-                        if let (Some(instance_field), Some(static_table), Some(sdgerp_field)) = (instance_field, static_table, sdgerp_field) {
-                            let instance_addr = static_table.add(instance_field.into());
-                            panel_loc = Some(StaticField{process: &process, base_address: instance_addr, field_offset: sdgerp_field.into()})
-                        }
+                stage_loc.resolve(&process, &monomod, &ror2);
+                panel_loc.resolve(&process, &monomod, &ror2);
+
+                // recover from selecting Assembly-CSharp.dll before RoR2.dll was loaded
+                // classes that are None are looked up again in the new image on the next tick,
+                // this only happens while none of the core classes were found, so nothing cached belongs to the old image
+                if ftbm.is_none() && !stage_loc.class_loaded() && !panel_loc.class_loaded() && self.game_state.scene.pair.is_some() {
+                    missing_class_ticks += 1;
+                } else {
+                    missing_class_ticks = 0;
+                }
+                if missing_class_ticks as f64 >= IMAGE_RETRY_SECONDS * TICK_RATE {
+                    if let Some(image) = monomod.get_image(&process, "RoR2").or(monomod.get_default_image(&process)) {
+                        ror2 = image;
                     }
+                    missing_class_ticks = 0;
+                }
+                trial_loc.resolve(&process, &monomod, &ror2);
+                teleporter_loc.resolve(&process, &monomod, &ror2);
+                mountain_loc.resolve(&process, &monomod, &ror2);
+                teleporter_charging_loc.resolve(&process, &monomod, &ror2);
+                simulacrum_wave_loc.resolve(&process, &monomod, &ror2);
+                lunar_coins_loc.resolve(&process, &monomod, &ror2);
+                body_preference_loc.resolve(&process, &monomod, &ror2);
+                body_names_loc.resolve(&process, &monomod, &ror2);
+                arena_loc.resolve(&process, &monomod, &ror2);
+                fixed_time_loc.resolve(&process, &monomod, &ror2);
+                phase_loc.resolve(&process, &monomod, &ror2);
+                run_time_loc.resolve(&process, &monomod, &ror2);
+                stopwatch_loc.resolve(&process, &monomod, &ror2);
+                difficulty_loc.resolve(&process, &monomod, &ror2);
+                equipment_loc.resolve(&process, &monomod, &ror2);
+                local_player_dead_loc.resolve(&process, &monomod, &ror2);
+                player_count_loc.resolve(&process, &monomod, &ror2);
+                gold_loc.resolve(&process, &monomod, &ror2);
+                item_stacks_loc.resolve(&process, &monomod, &ror2);
+                for heresy_loc in heresy_locs.iter_mut() {
+                    heresy_loc.resolve(&process, &monomod, &ror2);
+                }
+                red_item_list_loc.resolve(&process, &monomod, &ror2);
+                for route_item_loc in route_item_locs.iter_mut() {
+                    route_item_loc.resolve(&process, &monomod, &ror2);
                 }
 
                 // update game state watchers
-                // make old = current when updating from an invalid state
-                if alpha_loc.is_some() {
-                    if self.game_state.fade.pair.is_none() {
-                        self.game_state.fade.update( process.read::<f32>(alpha_loc.unwrap()).ok() );
-                    }
-                    self.game_state.fade.update( process.read::<f32>(alpha_loc.unwrap()).ok() );
+                self.update_fade(alpha_loc.and_then(|alpha_loc| process.read::<f32>(alpha_loc).ok()));
+
+                self.update_run(stage_loc.instance(), stage_loc.read::<i32>(), panel_loc.read::<bool>());
+
+                if let Some(boss_loc) = boss_loc.as_ref() {
+                    self.game_state.boss_groups.update( boss_loc.read_value::<i32>().ok() );
                 } else {
-                    self.game_state.fade.update(None);
+                    self.game_state.boss_groups.update(None);
                 }
 
-                if let Some(stage_loc) = stage_loc.as_ref() {
-                    if self.game_state.stage_count.pair.is_none() {
-                        self.game_state.stage_count.update( stage_loc.read_value::<i32>().ok() );
-                    }
-                    self.game_state.stage_count.update( stage_loc.read_value::<i32>().ok() );
+                let teleporter_state = teleporter_loc.read::<u32>();
+                self.game_state.teleporter_charged.update( teleporter_state.map(|state| state >= TELEPORTER_CHARGED) );
+                // the charge can only pause while charging, finishing the charge is not a pause
+                if teleporter_state == Some(TELEPORTER_CHARGING) {
+                    self.game_state.teleporter_charging.update( teleporter_charging_loc.read::<bool>(&process) );
                 } else {
-                    self.game_state.stage_count.update(None);
+                    self.game_state.teleporter_charging.update(None);
                 }
+                // like stageClearCount, only meaningful during a run
+                self.game_state.mountain_shrines.update( self.game_state.stage_count.pair.and(mountain_loc.read::<i32>()) );
+                self.update_teleporter_count();
 
-                if let Some(panel_loc) = panel_loc.as_ref() {
-                    if self.game_state.results.pair.is_none() {
-                        self.game_state.results.update( panel_loc.read_value::<bool>().ok() );
-                    }
-                    self.game_state.results.update( panel_loc.read_value::<bool>().ok() );
-                } else {
-                    self.game_state.results.update(None);
+                self.game_state.lunar_coins.update( lunar_coins_loc.read::<u32>(&process) );
+                self.game_state.survivor.update( read_body_name(&process, &body_names_loc, &body_preference_loc) );
+                // like stageClearCount, only meaningful once the run has started
+                self.game_state.difficulty.update( self.game_state.stage_count.pair.and(difficulty_loc.read::<i32>()) );
+                self.game_state.run_fixed_time.update( fixed_time_loc.read::<f32>().filter(|time| time.is_finite()) );
+                self.game_state.run_stopwatch.update( run_stopwatch(stopwatch_loc.read::<[u8; 8]>(), self.game_state.run_fixed_time.pair.map(|time| time.current)) );
+                self.game_state.run_time.update( run_time_loc.read::<f32>().filter(|time| time.is_finite()) );
+                self.game_state.equipment_stock.update( equipment_loc.read::<i32>(&process) );
+                // masters persist across stages, but only exist during a run
+                self.game_state.local_player_dead.update( self.game_state.stage_count.pair.and(local_player_dead_loc.read::<bool>(&process)) );
+                self.game_state.player_count.update( self.game_state.stage_count.pair.and(player_count_loc.read::<i32>(&process)) );
+                self.game_state.gold.update( self.game_state.stage_count.pair.and(gold_loc.read::<u32>(&process)) );
+
+                self.update_gold_milestones();
+                self.game_state.holds_heresy_item = holds_any_item(&process, &item_stacks_loc, &heresy_locs);
+
+                // only the first pickup of the route item in a run counts
+                if self.game_state.route_item_choice != Some(self.settings.split_item) {
+                    self.game_state.route_item = Watcher::default();
+                    self.game_state.route_item_choice = Some(self.settings.split_item);
+                }
+                self.game_state.route_item.update( item_count(&process, &item_stacks_loc, &route_item_locs[self.settings.split_item.index()]) );
+                let in_run = self.game_state.stage_count.pair.is_some();
+                self.game_state.route_item_acquired = first_pickup(&self.game_state.route_item, in_run, &mut self.game_state.route_item_held);
+                self.game_state.red_items.update( self.game_state.stage_count.pair.and(count_items_in_list(&process, &item_stacks_loc, &red_item_list_loc)) );
+                self.game_state.first_red_acquired = first_pickup(&self.game_state.red_items, in_run, &mut self.game_state.red_item_held);
+                self.update_bazaar_purchases();
+
+                // the cell count is only meaningful inside Void Fields, a fresh visit starts invalid
+                match self.game_state.scene.pair {
+                    Some(scene) if scene.current.as_str() == "arena" => {
+                        self.game_state.arena_cleared_rounds.update( arena_loc.read::<i32>() );
+                    },
+                    _ => { self.game_state.arena_cleared_rounds.update(None); }
+                }
+
+                // the Run instance is only an InfiniteTowerRun on Simulacrum stages
+                match self.game_state.scene.pair {
+                    Some(scene) if scene.current.starts_with("it") => {
+                        self.game_state.simulacrum_wave.update( simulacrum_wave_loc.read::<i32>(&process) );
+                    },
+                    _ => { self.game_state.simulacrum_wave.update(None); }
+                }
+
+                // the phase is only meaningful in Commencement, a fresh visit starts invalid
+                match self.game_state.scene.pair {
+                    Some(scene) if scene.current.starts_with("moon") => {
+                        self.game_state.mithrix_phase.update( phase_loc.read::<i32>() );
+                    },
+                    _ => { self.game_state.mithrix_phase.update(None); }
+                }
+
+                // the trial state is only meaningful inside Bulwark's Ambry, a fresh visit starts invalid
+                match self.game_state.scene.pair {
+                    Some(scene) if scene.current.as_str() == "artifactworld" => {
+                        self.game_state.artifact_trial_complete.update( trial_loc.read::<bool>() );
+                    },
+                    _ => { self.game_state.artifact_trial_complete.update(None); }
                 }
 
                 // update the scene name
                 // skip scene name updates during scene transitions (always invalid)
-                if let Some(scene) = sceneman.get_current_scene_path::<256>(&process).ok() {
+                // and when a partial/stale path was read, keeping the last valid scene
+                if let Some(scene) = sceneman.get_current_scene_path::<SCENE_PATH_LEN>(&process).ok() {
                     let utf8_scene = std::str::from_utf8(get_scene_name(scene.as_bytes())).unwrap_or_default();
-                    self.game_state.scene.update(ArrayString::<16>::from(&utf8_scene).ok());
+                    if is_valid_scene_name(utf8_scene) {
+                        match ArrayString::<SCENE_NAME_LEN>::from(&utf8_scene) {
+                            Ok(scene) => { self.game_state.scene.update(Some(scene)); },
+                            // keeps the last valid scene, warns once per scene
+                            Err(_) => {
+                                if long_scene.as_deref() != Some(utf8_scene) {
+                                    asr::print_message(&format!("{}: scene name \"{}\" is longer than {} characters, keeping the last scene", INFO.display_name, utf8_scene, SCENE_NAME_LEN));
+                                    long_scene = Some(utf8_scene.to_owned());
+                                }
+                            },
+                        }
+                    }
+                }
+                scene_var.update(self.game_state.scene.pair.map(|scene| scene.current));
+                self.update_scene_progress();
+
+                // read-only information for verifiers, cleared outside of a run
+                legal_var.update(self.legal());
+                obelisk_var.update(self.obelisk_available());
+                fixed_time_var.update(self.game_state.run_fixed_time.pair.and_then(|time| ArrayString::<16>::from(&game::format_time(Duration::seconds_f32(time.current))).ok()));
+                run_time_var.update(self.game_state.run_time.pair.and_then(|time| ArrayString::<16>::from(&game::format_time(Duration::seconds_f32(time.current))).ok()));
+                drift_var.update(self.run_time_drift_ms());
+                teleporter_charging_var.update(self.game_state.teleporter_charging.pair.filter(|_| autosplitter.debug()).map(|charging| charging.current));
+                survivor_var.update(self.game_state.survivor.pair.filter(|_| autosplitter.debug()).map(|survivor| survivor.current));
+                gold_var.update(self.game_state.gold.pair.filter(|_| autosplitter.debug()).map(|gold| gold.current));
+                loop_var.update(self.game_state.stage_count.pair.filter(|_| autosplitter.debug()).map(|_| self.game_state.loop_count));
+                stage_var.update(self.game_state.scene.pair.and_then(|scene| ArrayString::<SCENE_NAME_LEN>::from(stage_display_name(&scene.current)).ok()));
+
+                if autosplitter.diagnostics_requested() {
+                    self.diagnostics.paths = vec![
+                        ("FadeToBlackManager.alpha", alpha_loc.map(|alpha_loc| (alpha_loc, Vec::new()))),
+                        ("BossGroup.instancesList.Count", boss_loc.as_ref().map(StaticField::path)),
+                        ("Run.stageClearCount", stage_loc.path()),
+                        ("Run.runStopwatch", stopwatch_loc.path()),
+                        ("Run.selectedDifficultyInternal", difficulty_loc.path()),
+                        ("GameOverController.shouldDisplayGameEndReportPanels", panel_loc.path()),
+                        ("ArtifactTrialMissionController.missionCompleted", trial_loc.path()),
+                        ("TeleporterInteraction.activationStateInternal", teleporter_loc.path()),
+                        ("TeleporterInteraction.shrineBonusStacks", mountain_loc.path()),
+                        ("ArenaMissionController.clearedRounds", arena_loc.path()),
+                        ("teleporter charging", teleporter_charging_loc.path()),
+                        ("Simulacrum wave", simulacrum_wave_loc.path()),
+                        ("lunar coins", lunar_coins_loc.path()),
+                        ("body index preference", body_preference_loc.path()),
+                        ("BodyCatalog.bodyNames", body_names_loc.path()),
+                        ("equipment stock", equipment_loc.path()),
+                        ("local player dead", local_player_dead_loc.path()),
+                        ("player count", player_count_loc.path()),
+                        ("gold", gold_loc.path()),
+                        ("item stacks", item_stacks_loc.path()),
+                    ];
+                    self.dump_diagnostics(process);
                 }
 
                 self.settings.update();
@@ -211,74 +2074,98 @@ impl game::GameAutoSplitter for Game {
         }
     }
 
-    /// Start on regular Stage 1s during fade-in
+    /// Start on regular Stage 1s during fade-in, or when the first wave starts in Simulacrum
+    ///
+    /// Never starts while disarmed, or with a survivor or difficulty other than the required one.
+    /// Looping back to a Stage 1 is not a new run: stages have already been cleared, so it never starts (or resets,
+    /// Stage 1 scenes are not reset scenes) even while the timer is waiting for a game swap.
     fn start(&self) -> bool {
-        if let (Some(scene), Some(fade)) = (self.game_state.scene.pair, self.game_state.fade.pair) {
-            if scene.current.starts_with("golemplains") ||
-               scene.current.starts_with("blackbeach") ||
-               scene.current.starts_with("snowyforest") ||
-               scene.current.starts_with("lakes") ||
-               scene.current.starts_with("village")
-            {
-                return fade.current < 1.0 && fade.old >= 1.0;
+        if !self.settings.arm_start {
+            return false;
+        }
+        if let Some(stage_count) = self.game_state.stage_count.pair {
+            if stage_count.current > 0 {
+                return false;
             }
         }
-        return false;
+        if let (Some(survivor), Some(required)) = (self.game_state.survivor.pair, self.settings.ror2_required_survivor.body_name()) {
+            if survivor.current.as_str() != required {
+                return false;
+            }
+        }
+        if let Some(difficulty) = self.game_state.difficulty.pair {
+            if !self.settings.ror2_require_difficulty.matches(difficulty.current) {
+                return false;
+            }
+        }
+        return self.run_started();
     }
 
-    /// Reset on certain menu screens
+    /// Reset on certain menu screens, or optionally on a game over
     fn reset(&self) -> bool {
         if let Some(scene) = self.game_state.scene.pair {
+            // GameOverController only exists after a game over, a revived death never reaches this state
+            // game overs on the ending scenes and in Simulacrum are completions instead
+            if self.settings.reset_on_death {
+                if let Some(results) = self.game_state.results.pair {
+                    let ending_scene = scene_kind(&scene.current) == Some(SceneKind::Ending);
+                    if results.changed_to(&true) && !ending_scene && self.detected_category() != Some(Category::Simulacrum) {
+                        return true;
+                    }
+                }
+            }
             return match scene.current.as_str() {
-                "lobby" | "loadingbasic" | "intro" | "title" | "crystalworld" | "eclipseworld" | "infinitetowerworld"
-                    => true,
-                _ => false
+                "lobby" => self.settings.reset_on_lobby,
+                "infinitetowerworld" => self.settings.reset_on_infinitetowerworld,
+                "crystalworld" => self.settings.reset_on_crystalworld,
+                "eclipseworld" => self.settings.reset_on_eclipseworld,
+                scene => scene_kind(scene) == Some(SceneKind::Menu),
             }
         }
         return false;
     }
 
-    /// Split on stage increment, and special scenes, ignoring game end conditions
-    fn split(&self) -> bool {
-        // stage count increased
-        if self.settings.ror2_stages {
-            if let Some(stage_count) = self.game_state.stage_count.pair {
-                if stage_count.current >= 1 && stage_count.increased() {
-                    // avoid double splits on Commencement
-                    return match self.game_state.scene.pair {
-                        Some(scene) => !scene.current.starts_with("moon"),
-                        _ => true
-                    }
-                }
-            }
-        }
+    /// No resets while on or just leaving the outro cutscene
+    fn reset_allowed(&self) -> bool {
         if let Some(scene) = self.game_state.scene.pair {
-            // reached a special scene
-            if scene.changed() {
-                match scene.old.as_str() {
-                    "bazaar" => return self.settings.bazaar,
-                    "arena" => return self.settings.arena,
-                    "goldshores" => return self.settings.goldshores,
-                    "artifactworld" => return self.settings.artifactworld,
-                    _ => ()
-                }
-            }
+            return scene.old.as_str() != "outro" && scene.current.as_str() != "outro";
         }
-        return false;
+        return true;
+    }
+
+    /// Split on stage increment, special scenes, and run events, ignoring game end conditions
+    ///
+    /// At most one split fires per tick. When several conditions are met on the same tick the priority is:
+    /// completion (never a split, see `completed()`) > special scene > stage increment > run events
+    fn split(&self) -> bool {
+        if self.completed() {
+            return false;
+        }
+        return self.special_scene_split() || self.stage_split() || self.loop_split() || self.event_split();
     }
 
     /// Completed when the scene is the outro cutscene or if the game end was triggered for CE/SotV alt endings.
+    ///
+    /// In multiplayer, alt endings only complete when the local player is still alive.
+    /// Simulacrum runs complete when the game end is triggered on any Simulacrum stage.
+    /// Optionally completes on killing Voidling, before the ending plays.
     fn completed(&self) -> bool {
+        if self.settings.ror2_voidling_kill == VoidlingKill::Complete && self.voidling_killed() {
+            return true;
+        }
         if let Some(scene) = self.game_state.scene.pair {
             if scene.changed() && scene.current.as_str() == "outro" {
                 return true;
             }
-            // completed a run on specific scenes
+            // completed a run on an ending scene, the outro already completed when it was entered
             if let Some(results) = self.game_state.results.pair {
                 if results.changed_to(&true) {
-                    match scene.current.as_str() {
-                        "limbo" | "mysteryspace" | "voidraid" => return true,
-                        _ => ()
+                    if scene_kind(&scene.current) == Some(SceneKind::Ending) && self.local_player_reached_ending() {
+                        return true;
+                    }
+                    // Simulacrum has no outro, the run ends on the results screen
+                    if self.detected_category() == Some(Category::Simulacrum) {
+                        return true;
                     }
                 }
             }
@@ -286,19 +2173,900 @@ impl game::GameAutoSplitter for Game {
         return false;
     }
 
-    /// Game is loading when FadeToBlackManager.alpha is increasing from 0->2.0 or at 2.0
+    /// Game is loading when FadeToBlackManager.alpha is increasing from 0->2.0 or at 2.0,
+    /// or optionally after the run ended until the next run starts
     ///
-    /// Sometimes this is undetermined when updates are too quick, or the game lags
+    /// Sometimes this is undetermined when updates are too quick, or the game lags.
+    /// Optionally settled from the fade alone once it stays undetermined for too long.
     fn is_loading(&self) -> Option<bool> {
-        if let Some(fade) = self.game_state.fade.pair {
-            if fade.increased() {
-                return Some(true);
+        if self.settings.pause_on_results && self.game_state.run_ended {
+            return Some(true);
+        }
+        if let Some(timeout) = self.settings.undetermined_loading_timeout.ticks() {
+            if self.game_state.undetermined_fade_ticks >= timeout {
+                return self.game_state.fade.pair.map(|fade| fade.current > 0.0);
             }
-            if fade.decreased() && fade.current > 0.0 || fade.current == 0.0 {
-                return Some(false);
+        }
+        return self.fade_loading();
+    }
+
+    fn soft_reset(&mut self) {
+        self.reset_state();
+    }
+
+    fn dump_diagnostics(&self, process: &Process) {
+        self.diagnostics.print(self.display_name(), process, PointerSize::Bit64);
+    }
+
+    /// Current stage number, including loops (stageClearCount starts at 0 on Stage 1)
+    fn progress(&self) -> Option<ArrayString<32>> {
+        let stage_count = self.game_state.stage_count.pair?;
+        return game::format_progress("stage", stage_count.current + 1);
+    }
+
+    /// Load removal based on FadeToBlackManager.alpha, or game time synced to the run timer
+    ///
+    /// The run timer is unavailable outside of a run, fade based load removal covers that time instead
+    fn load_strategy(&self) -> game::LoadStrategy {
+        if self.settings.ror2_fixed_time_sync && self.game_state.run_stopwatch.pair.is_some() {
+            return game::LoadStrategy::InGameTime;
+        }
+        return game::LoadStrategy::FadeBased;
+    }
+
+    /// Run.instance.GetRunStopwatch(), invalid outside of a run
+    ///
+    /// Counts from the start of this run, the autosplitter adds the game time of earlier games in a multi-game run
+    fn game_time(&self) -> Option<Duration> {
+        return self.game_state.run_stopwatch.pair.map(|time| Duration::seconds_f32(time.current));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::autosplitter::golden::{self, Reads, Replay};
+
+    fn settings() -> GameSettings {
+        return GameSettings {
+            _ror2_settings: Title,
+            category_presets: false,
+            ror2_stages: true,
+            unique_stage_splits: false,
+            ror2_loop_splits: false,
+            arm_start: true,
+            ror2_start_on_run: false,
+            ror2_required_survivor: RequiredSurvivor::Any,
+            ror2_require_difficulty: RequiredDifficulty::Any,
+            fade_start_tolerance: FadeTolerance::Strict,
+            undetermined_loading_timeout: LoadingTimeout::Never,
+            ror2_fixed_time_sync: false,
+            reset_on_lobby: true,
+            reset_on_crystalworld: true,
+            reset_on_eclipseworld: true,
+            reset_on_infinitetowerworld: true,
+            reset_on_death: false,
+            pause_on_results: false,
+            commencement_enter_split: false,
+            voidraid_enter_split: false,
+            ror2_voidling_kill: VoidlingKill::ResultsScreen,
+            bazaar_enter: false,
+            bazaar_exit: false,
+            bazaar_purchase_splits: false,
+            equipment_use_splits: false,
+            ror2_first_red: false,
+            split_on_item: false,
+            split_item: RouteItem::BeadsOfFealty,
+            arena_enter: false,
+            arena_exit: false,
+            void_fields_complete_split: false,
+            ror2_arena_cells: false,
+            goldshores_enter: false,
+            goldshores_exit: false,
+            artifactworld_enter: false,
+            artifactworld_exit: false,
+            artifactworld_on_success: false,
+            obliterate_initiate_split: false,
+            any_boss_splits: false,
+            ror2_teleporter: false,
+            ror2_mithrix_phases: false,
+            ror2_simulacrum_waves: false,
+            split_on_teleporter_count: TeleporterCount::Off,
+            split_on_gold: GoldMilestone::Off,
+            teleporter_pause_splits: false,
+            ror2_mountain_shrines: false,
+            _ror2_stage_splits: Title,
+            split_golemplains: false,
+            split_blackbeach: false,
+            split_snowyforest: false,
+            split_lakes: false,
+            split_village: false,
+            split_goolake: false,
+            split_foggyswamp: false,
+            split_ancientloft: false,
+            split_lemuriantemple: false,
+            split_frozenwall: false,
+            split_wispgraveyard: false,
+            split_sulfurpools: false,
+            split_habitat: false,
+            split_dampcavesimple: false,
+            split_shipgraveyard: false,
+            split_rootjungle: false,
+            split_skymeadow: false,
+            split_helminthroost: false,
+            split_meridian: false,
+        };
+    }
+
+    fn new_game() -> Game {
+        return Game { settings: settings(), game_state: GameVars::default(), diagnostics: game::Diagnostics::default() };
+    }
+
+    /// Game fully faded in on `scene` during a run with `stage_count` stages cleared
+    fn in_run(settings: GameSettings, scene: &str, stage_count: i32) -> Game {
+        let mut game = Game { settings, ..new_game() };
+        game.update_fade(Some(0.0));
+        game.update_run(Some(Address::new(1000)), Some(stage_count), Some(false));
+        enter(&mut game, scene);
+        return game;
+    }
+
+    /// Moves to `scene` on the next tick, every other read stays the same
+    fn enter(game: &mut Game, scene: &str) {
+        game.game_state.scene.update(ArrayString::from(scene).ok());
+        game.update_scene_progress();
+    }
+
+    impl Replay for Game {
+        fn replay_tick(&mut self, reads: &Reads) {
+            self.update_fade(reads.get("fade"));
+            self.update_run(reads.get("run").map(Address::new), reads.get("stage_count"), reads.get("results"));
+            // an invalid scene read keeps the last valid scene
+            if let Some(scene) = reads.get::<String>("scene") {
+                self.game_state.scene.update(ArrayString::from(&scene).ok());
             }
+            self.update_scene_progress();
         }
-        // maintain previous state when fade in/out is undetermined (aka current == previous)
-        return None;
+    }
+
+    #[test]
+    fn seeded_watchers_never_change_when_becoming_valid() {
+        let mut watcher = Watcher::default();
+        update_seeded(&mut watcher, Some(2.0));
+        assert!(!watcher.pair.unwrap().changed());
+        update_seeded(&mut watcher, Some(1.5));
+        assert!(watcher.pair.unwrap().decreased());
+        update_seeded(&mut watcher, None);
+        assert!(watcher.pair.is_none());
+        update_seeded(&mut watcher, Some(0.0));
+        assert!(!watcher.pair.unwrap().changed());
+        assert_eq!(watcher.pair.unwrap().current, 0.0);
+    }
+
+    #[test]
+    fn every_known_scene_name_fits_a_stored_scene() {
+        for (name, _) in SCENES {
+            assert!(ArrayString::<SCENE_NAME_LEN>::from(name).is_ok(), "{} does not fit", name);
+        }
+    }
+
+    #[test]
+    fn scene_names_are_validated() {
+        assert!(is_valid_scene_name("infinitetowerworld"));
+        assert!(is_valid_scene_name("golemplains2"));
+        assert!(is_valid_scene_name("FBLScene"));
+        assert!(is_valid_scene_name("itgolemplains_1"));
+        assert!(!is_valid_scene_name(""));
+        assert!(!is_valid_scene_name("Assets/RoR2/Scenes"));
+        assert!(!is_valid_scene_name("\u{0}\u{0}"));
+    }
+
+    #[test]
+    fn stage_one_includes_variants_and_dlc_stages() {
+        for scene in ["golemplains", "golemplains2", "blackbeach2", "snowyforest", "lakes", "lakesnight", "village", "villagenight"] {
+            assert!(is_stage_one(scene), "{}", scene);
+        }
+        for scene in ["goolake", "frozenwall", "moon2", "bazaar", "lobby", "infinitetowerworld"] {
+            assert!(!is_stage_one(scene), "{}", scene);
+        }
+    }
+
+    #[test]
+    fn golden_run() {
+        golden::replay(&mut new_game(), include_str!("golden/ror2.golden"));
+    }
+
+    #[test]
+    fn looped_stage_one_never_starts_or_resets() {
+        // attached during a run with the timer stopped, so a start on the looped Stage 1 would show up
+        let mut game = new_game();
+        golden::replay(&mut game, "
+            scene=skymeadow fade=0 run=1000 stage_count=4 results=false
+            fade=2
+            scene=moon2 stage_count=5
+            fade=0
+            fade=2
+            scene=golemplains               # looped back to Stage 1
+            fade=1.5
+            fade=0.5                        # fading in
+        ");
+        assert!(!game.start());
+        assert!(!game.reset());
+    }
+
+    #[test]
+    fn special_scene_and_stage_count_on_the_same_tick_split_once() {
+        let mut game = Game { settings: GameSettings { bazaar_exit: true, ..settings() }, ..new_game() };
+        golden::replay(&mut game, "
+            scene=golemplains fade=2 run=1000 stage_count=0 results=false
+            fade=0.5 => start
+            fade=0
+            fade=2
+            scene=bazaar                    # the Bazaar is not a stage
+            fade=0
+            fade=2
+            scene=foggyswamp stage_count=1 => split
+        ");
+        assert!(game.special_scene_split());
+        assert!(game.stage_split());
+    }
+
+    #[test]
+    fn back_to_back_runs_never_split_on_a_stale_stage_count() {
+        golden::replay(&mut new_game(), "
+            scene=golemplains fade=2 run=1000 stage_count=0 results=false
+            fade=0.5 => start
+            fade=0
+            fade=2
+            scene=goolake stage_count=1 => split
+            fade=0
+            fade=2
+            scene=blackbeach run=2000 stage_count=3     # restarted, the new Run briefly holds a stale count
+            stage_count=0
+            fade=0.5
+            fade=0
+            fade=2
+            scene=foggyswamp stage_count=1 => split     # the second run splits from its own count
+        ");
+    }
+
+    #[test]
+    fn artifact_trial_splits_on_success_but_not_when_fleeing() {
+        let mut game = in_run(GameSettings { artifactworld_on_success: true, ..settings() }, "artifactworld", 2);
+        game.game_state.artifact_trial_complete.update(Some(false));
+        assert!(!game.split());
+        game.game_state.artifact_trial_complete.update(Some(true));
+        assert!(game.split());
+
+        // fled the trial, the trial state is invalid outside of Bulwark's Ambry
+        let mut game = in_run(GameSettings { artifactworld_on_success: true, ..settings() }, "artifactworld", 2);
+        game.game_state.artifact_trial_complete.update(Some(false));
+        enter(&mut game, "goolake");
+        game.game_state.artifact_trial_complete.update(None);
+        assert!(!game.split());
+
+        // leaving splits on its own setting either way
+        let mut game = in_run(GameSettings { artifactworld_exit: true, ..settings() }, "artifactworld", 2);
+        game.game_state.artifact_trial_complete.update(Some(true));
+        assert!(!game.split());
+        enter(&mut game, "goolake");
+        game.game_state.artifact_trial_complete.update(None);
+        assert!(game.split());
+    }
+
+    #[test]
+    fn every_boss_kill_splits_once() {
+        let mut game = in_run(GameSettings { any_boss_splits: true, ..settings() }, "goolake", 1);
+        // the teleporter boss and a shrine boss, each killed in turn
+        for (boss_groups, split) in [(2, false), (1, true), (1, false), (2, false), (1, true), (0, true)] {
+            game.game_state.boss_groups.update(Some(boss_groups));
+            assert_eq!(game.split(), split, "{} boss groups", boss_groups);
+        }
+
+        // boss groups are cleaned up when leaving the stage, during the fade out or on the scene change
+        game.game_state.boss_groups.update(Some(2));
+        game.update_fade(Some(1.0));
+        game.game_state.boss_groups.update(Some(1));
+        assert!(!game.split());
+        game.game_state.boss_groups.update(Some(0));
+        enter(&mut game, "frozenwall");
+        assert!(!game.split());
+    }
+
+    #[test]
+    fn disarmed_start_still_splits_and_resets() {
+        let mut game = Game { settings: GameSettings { arm_start: false, ..settings() }, ..new_game() };
+        golden::replay(&mut game, "
+            scene=golemplains fade=2 run=1000 stage_count=0 results=false
+            fade=0.5                        # Stage 1 fading in
+        ");
+        assert!(game.run_started());
+        assert!(!game.start());
+
+        let mut game = in_run(GameSettings { arm_start: false, ..settings() }, "golemplains", 0);
+        game.update_run(Some(Address::new(1000)), Some(1), Some(false));
+        enter(&mut game, "goolake");
+        assert!(game.split());
+        game.update_run(None, None, None);
+        enter(&mut game, "lobby");
+        assert!(game.reset());
+    }
+
+    #[test]
+    fn entering_commencement_splits_once() {
+        for ror2_stages in [true, false] {
+            let mut game = in_run(GameSettings { commencement_enter_split: true, ror2_stages, ..settings() }, "skymeadow", 4);
+            game.update_run(Some(Address::new(1000)), Some(5), Some(false));
+            enter(&mut game, "moon2");
+            assert!(game.split(), "stage splits {}", ror2_stages);
+            enter(&mut game, "moon2");
+            assert!(!game.split(), "stage splits {}", ror2_stages);
+        }
+    }
+
+    #[test]
+    fn revived_deaths_never_reset() {
+        let mut game = in_run(GameSettings { reset_on_death: true, ..settings() }, "goolake", 1);
+        // died and was revived by Dio's Best Friend, no game over
+        game.game_state.local_player_dead.update(Some(false));
+        game.game_state.local_player_dead.update(Some(true));
+        game.game_state.local_player_dead.update(Some(false));
+        assert!(!game.reset());
+
+        // game over
+        game.game_state.local_player_dead.update(Some(true));
+        game.update_run(Some(Address::new(1000)), Some(1), Some(true));
+        assert!(game.reset());
+
+        // dying on an ending scene is not a reset
+        let mut game = in_run(GameSettings { reset_on_death: true, ..settings() }, "mysteryspace", 5);
+        game.update_run(Some(Address::new(1000)), Some(5), Some(true));
+        assert!(!game.reset());
+    }
+
+    #[test]
+    fn category_presets_follow_the_detected_category() {
+        for category_presets in [true, false] {
+            // classic run, stage transitions split with the preset
+            let mut game = in_run(GameSettings { category_presets, ror2_stages: false, ..settings() }, "golemplains", 0);
+            game.update_run(Some(Address::new(1000)), Some(1), Some(false));
+            enter(&mut game, "goolake");
+            assert_eq!(game.split(), category_presets);
+
+            // Simulacrum run, boss kills split with the preset
+            let mut game = in_run(GameSettings { category_presets, ror2_stages: false, ..settings() }, "itgolemplains", 0);
+            game.game_state.boss_groups.update(Some(1));
+            game.game_state.boss_groups.update(Some(0));
+            assert_eq!(game.split(), category_presets);
+        }
+
+        // toggles enabled by the user stay enabled in every category
+        let game = in_run(GameSettings { category_presets: true, ror2_stages: true, ..settings() }, "itgolemplains", 0);
+        assert!(game.effective_settings().ror2_stages);
+        assert!(game.effective_settings().any_boss_splits);
+    }
+
+    #[test]
+    fn simulacrum_runs_complete_on_the_results_screen() {
+        let mut game = in_run(GameSettings { reset_on_death: true, ..settings() }, "itgolemplains", 0);
+        assert!(!game.completed());
+        game.update_run(Some(Address::new(1000)), Some(0), Some(true));
+        assert!(game.completed());
+        assert!(!game.reset());
+
+        // a classic run ending on a regular stage is a game over instead
+        let mut game = in_run(GameSettings { reset_on_death: true, ..settings() }, "golemplains", 0);
+        game.update_run(Some(Address::new(1000)), Some(0), Some(true));
+        assert!(!game.completed());
+        assert!(game.reset());
+    }
+
+    #[test]
+    fn teleporter_count_splits_once_at_the_target() {
+        let mut game = in_run(GameSettings { split_on_teleporter_count: TeleporterCount::Five, ..settings() }, "golemplains", 0);
+        for teleporter in 1..=6 {
+            game.game_state.teleporter_charged.update(Some(false));
+            game.update_teleporter_count();
+            assert!(!game.split());
+            game.game_state.teleporter_charged.update(Some(true));
+            game.update_teleporter_count();
+            assert_eq!(game.split(), teleporter == 5, "teleporter {}", teleporter);
+            // the next stage has a new teleporter
+            game.game_state.teleporter_charged.update(None);
+            game.update_teleporter_count();
+        }
+        assert_eq!(game.game_state.teleporter_count, 6);
+
+        // the next run counts from zero
+        game.update_run(None, None, None);
+        game.update_teleporter_count();
+        assert_eq!(game.game_state.teleporter_count, 0);
+    }
+
+    #[test]
+    fn bazaar_purchases_split_and_count_per_visit() {
+        let mut game = in_run(GameSettings { bazaar_purchase_splits: true, ..settings() }, "goolake", 1);
+        game.game_state.lunar_coins.update(Some(10));
+        // lunar coins spent outside of the Bazaar
+        game.game_state.lunar_coins.update(Some(8));
+        game.update_bazaar_purchases();
+        assert!(!game.split());
+
+        enter(&mut game, "bazaar");
+        game.update_bazaar_purchases();
+        assert!(!game.split());
+        enter(&mut game, "bazaar");
+        for (lunar_coins, split) in [(6, true), (6, false), (7, false), (5, true)] {
+            game.game_state.lunar_coins.update(Some(lunar_coins));
+            game.update_bazaar_purchases();
+            assert_eq!(game.split(), split, "{} lunar coins", lunar_coins);
+        }
+        assert_eq!(game.game_state.bazaar_purchases, 2);
+
+        // the next visit counts from zero
+        enter(&mut game, "frozenwall");
+        enter(&mut game, "bazaar");
+        game.update_bazaar_purchases();
+        assert_eq!(game.game_state.bazaar_purchases, 0);
+    }
+
+    #[test]
+    fn void_fields_clear_and_leave_split_separately() {
+        // cleared every cell, then left
+        let mut game = in_run(GameSettings { void_fields_complete_split: true, ..settings() }, "arena", 1);
+        for cleared_rounds in 0..ARENA_ROUNDS {
+            game.game_state.arena_cleared_rounds.update(Some(cleared_rounds));
+            assert!(!game.split(), "{} cells", cleared_rounds);
+        }
+        game.game_state.arena_cleared_rounds.update(Some(ARENA_ROUNDS));
+        assert!(game.split());
+        enter(&mut game, "goolake");
+        game.game_state.arena_cleared_rounds.update(None);
+        assert!(!game.split());
+
+        // left before clearing every cell
+        let mut game = in_run(GameSettings { void_fields_complete_split: true, arena_exit: true, ..settings() }, "arena", 1);
+        game.game_state.arena_cleared_rounds.update(Some(3));
+        enter(&mut game, "goolake");
+        game.game_state.arena_cleared_rounds.update(None);
+        assert!(game.split());
+    }
+
+    #[test]
+    fn every_equipment_use_splits_once() {
+        let mut game = in_run(GameSettings { equipment_use_splits: true, ..settings() }, "goolake", 1);
+        // picked up with two charges, used twice, then recharged
+        for (stock, split) in [(2, false), (1, true), (1, false), (0, true), (0, false), (1, false)] {
+            game.game_state.equipment_stock.update(Some(stock));
+            assert_eq!(game.split(), split, "stock {}", stock);
+        }
+        // the body is gone between stages
+        game.game_state.equipment_stock.update(None);
+        game.game_state.equipment_stock.update(Some(0));
+        assert!(!game.split());
+    }
+
+    #[test]
+    fn results_screen_pauses_until_the_next_run_starts() {
+        for pause_on_results in [true, false] {
+            let mut game = in_run(GameSettings { pause_on_results, ..settings() }, "goolake", 1);
+            game.update_run(Some(Address::new(1000)), Some(1), Some(true));
+            game.update_scene_progress();
+            assert_eq!(game.is_loading(), Some(pause_on_results));
+
+            game.update_run(None, None, None);
+            enter(&mut game, "lobby");
+            assert_eq!(game.is_loading(), Some(pause_on_results));
+
+            game.update_fade(Some(2.0));
+            game.update_run(Some(Address::new(2000)), Some(0), Some(false));
+            enter(&mut game, "golemplains");
+            assert_eq!(game.is_loading(), Some(true));
+            game.update_fade(Some(0.5));
+            game.update_scene_progress();
+            assert_eq!(game.is_loading(), Some(false));
+        }
+    }
+
+    #[test]
+    fn legality_follows_the_heresy_items_held() {
+        let mut game = in_run(settings(), "goolake", 1);
+        game.game_state.holds_heresy_item = any_item_held([Some(0), Some(0), Some(0), Some(0)]);
+        assert_eq!(game.legal(), Some(true));
+        game.game_state.holds_heresy_item = any_item_held([Some(0), Some(0), Some(2), Some(0)]);
+        assert_eq!(game.legal(), Some(false));
+        // no inventory before the run, or no item catalog yet
+        game.game_state.holds_heresy_item = any_item_held([None, None, None, None]);
+        assert_eq!(game.legal(), None);
+        game.game_state.holds_heresy_item = any_item_held([Some(1), None, Some(0), Some(0)]);
+        assert_eq!(game.legal(), None);
+    }
+
+    #[test]
+    fn teleporter_charge_pauses_split() {
+        let mut game = in_run(GameSettings { teleporter_pause_splits: true, ..settings() }, "goolake", 1);
+        for (charging, split) in [(true, false), (false, true), (false, false), (true, false), (false, true)] {
+            game.game_state.teleporter_charging.update(Some(charging));
+            assert_eq!(game.split(), split, "charging {}", charging);
+        }
+        // finishing the charge invalidates the watcher, which is not a pause
+        game.game_state.teleporter_charging.update(Some(true));
+        game.game_state.teleporter_charging.update(None);
+        assert!(!game.split());
+    }
+
+    #[test]
+    fn simulacrum_runs_start_at_the_first_wave() {
+        let mut game = new_game();
+        game.update_fade(Some(2.0));
+        game.update_run(Some(Address::new(1000)), Some(0), Some(false));
+        enter(&mut game, "itgolemplains");
+        // fading in on a Simulacrum stage is not the start
+        game.update_fade(Some(0.5));
+        assert!(!game.start());
+        game.update_fade(Some(0.0));
+        game.game_state.simulacrum_wave.update(Some(0));
+        assert!(!game.start());
+        game.game_state.simulacrum_wave.update(Some(1));
+        assert!(game.start());
+        game.game_state.simulacrum_wave.update(Some(2));
+        assert!(!game.start());
+    }
+
+    /// Ticks of a Stage 1 fade-in the run starts on
+    fn fade_in_start_ticks(fade_start_tolerance: FadeTolerance, fades: &[Option<f32>]) -> Vec<usize> {
+        let mut game = Game { settings: GameSettings { fade_start_tolerance, ..settings() }, ..new_game() };
+        game.update_run(Some(Address::new(1000)), Some(0), Some(false));
+        enter(&mut game, "golemplains");
+        return fades.iter().enumerate().filter_map(|(tick, &fade)| {
+            game.update_fade(fade);
+            return game.start().then_some(tick);
+        }).collect();
+    }
+
+    #[test]
+    fn fade_in_starts_once_at_any_sample_density() {
+        // low density, the fade skips past 1.0 in a single tick
+        assert_eq!(fade_in_start_ticks(FadeTolerance::Strict, &[Some(2.0), Some(0.5), Some(0.0)]), [1]);
+        // high density
+        let fades = [2.0, 1.8, 1.4, 1.0, 0.9, 0.6, 0.2, 0.0].map(Some);
+        assert_eq!(fade_in_start_ticks(FadeTolerance::Strict, &fades), [4]);
+        // missed reads around the crossing
+        let fades = [Some(2.0), None, None, Some(0.8), Some(0.4), Some(0.0)];
+        assert!(fade_in_start_ticks(FadeTolerance::Strict, &fades).is_empty());
+        assert_eq!(fade_in_start_ticks(FadeTolerance::Short, &fades), [3]);
+        assert_eq!(fade_in_start_ticks(FadeTolerance::Long, &fades), [3]);
+    }
+
+    #[test]
+    fn obliteration_splits_before_the_run_completes() {
+        let mut game = Game { settings: GameSettings { obliterate_initiate_split: true, ..settings() }, ..new_game() };
+        golden::replay(&mut game, "
+            scene=golemplains fade=2 run=1000 stage_count=0 results=false
+            fade=0.5 => start
+            fade=0
+            fade=2
+            scene=mysteryspace              # through a Celestial Portal
+            fade=0
+            fade=0.5 => split               # used the Obelisk
+            fade=2
+            results=true => complete
+        ");
+        assert_eq!(game.obelisk_available(), Some(false));
+    }
+
+    #[test]
+    fn run_time_drift_is_frame_minus_fixed_clock() {
+        let mut game = in_run(settings(), "goolake", 1);
+        assert_eq!(game.run_time_drift_ms(), None);
+        game.game_state.run_fixed_time.update(Some(120.25));
+        game.game_state.run_time.update(Some(120.5));
+        assert_eq!(game.run_time_drift_ms(), Some(250));
+        game.game_state.run_time.update(Some(119.875));
+        assert_eq!(game.run_time_drift_ms(), Some(-375));
+        game.game_state.run_fixed_time.update(None);
+        assert_eq!(game.run_time_drift_ms(), None);
+    }
+
+    #[test]
+    fn individual_stage_splits_only_split_their_stages() {
+        let settings = GameSettings { ror2_stages: false, split_frozenwall: true, split_golemplains: true, ..settings() };
+        let mut game = in_run(settings, "golemplains", 0);
+        let stages = [("goolake", false), ("frozenwall", true), ("dampcavesimple", false), ("skymeadow", false), ("golemplains2", true), ("foggyswamp", false)];
+        for (stage_count, (scene, split)) in (1..).zip(stages) {
+            game.update_run(Some(Address::new(1000)), Some(stage_count), Some(false));
+            enter(&mut game, scene);
+            assert_eq!(game.split(), split, "{}", scene);
+        }
+    }
+
+    #[test]
+    fn every_mithrix_phase_splits_once() {
+        let mut game = in_run(GameSettings { ror2_mithrix_phases: true, ..settings() }, "skymeadow", 4);
+        game.update_run(Some(Address::new(1000)), Some(5), Some(false));
+        enter(&mut game, "moon2");
+        assert!(!game.split());
+        enter(&mut game, "moon2");
+        // the phase is only read in Commencement, so the first phase becomes valid without a change
+        for (phase, split) in [(1, false), (1, false), (2, true), (2, false), (3, true), (4, true)] {
+            game.game_state.mithrix_phase.update(Some(phase));
+            assert_eq!(game.split(), split, "phase {}", phase);
+        }
+    }
+
+    #[test]
+    fn every_completed_simulacrum_wave_splits_once() {
+        let mut game = in_run(GameSettings { ror2_simulacrum_waves: true, ..settings() }, "itgolemplains", 0);
+        // the first wave starting is the start instead
+        for (wave, split) in [(0, false), (1, false), (1, false), (2, true), (3, true), (3, false)] {
+            game.game_state.simulacrum_wave.update(Some(wave));
+            assert_eq!(game.split(), split, "wave {}", wave);
+        }
+        // waves never reset, leaving for the Simulacrum menu does unless disabled
+        assert!(!game.reset());
+        for reset_on_infinitetowerworld in [true, false] {
+            let mut game = Game { settings: GameSettings { ror2_simulacrum_waves: true, reset_on_infinitetowerworld, ..settings() }, ..new_game() };
+            enter(&mut game, "infinitetowerworld");
+            assert_eq!(game.reset(), reset_on_infinitetowerworld);
+        }
+    }
+
+    #[test]
+    fn every_void_fields_cell_splits_once() {
+        let mut game = in_run(GameSettings { ror2_arena_cells: true, void_fields_complete_split: true, ..settings() }, "arena", 1);
+        game.game_state.arena_cleared_rounds.update(Some(0));
+        for cleared_rounds in 1..=ARENA_ROUNDS {
+            game.game_state.arena_cleared_rounds.update(Some(cleared_rounds));
+            assert!(game.split(), "{} cells", cleared_rounds);
+            game.game_state.arena_cleared_rounds.update(Some(cleared_rounds));
+            assert!(!game.split(), "{} cells", cleared_rounds);
+        }
+
+        // the count is invalid outside of Void Fields, a stale count on the next visit is not a capture
+        enter(&mut game, "goolake");
+        game.game_state.arena_cleared_rounds.update(None);
+        enter(&mut game, "arena");
+        game.game_state.arena_cleared_rounds.update(Some(ARENA_ROUNDS));
+        assert!(!game.split());
+    }
+
+    #[test]
+    fn unique_stage_splits_skip_stages_seen_this_run() {
+        let mut game = Game { settings: GameSettings { unique_stage_splits: true, ..settings() }, ..new_game() };
+        golden::replay(&mut game, "
+            scene=golemplains fade=2 run=1000 stage_count=0 results=false
+            fade=0.5 => start
+            scene=goolake stage_count=1 => split
+            scene=frozenwall stage_count=2 => split
+            scene=dampcavesimple stage_count=3 => split
+            scene=skymeadow stage_count=4 => split
+            scene=golemplains2 stage_count=5            # looped, Titanic Plains again
+            scene=goolake stage_count=6
+            scene=foggyswamp stage_count=7 => split     # not seen this run
+            scene=wispgraveyard stage_count=8 => split
+            scene=skymeadow stage_count=9
+        ");
+    }
+
+    #[test]
+    fn undetermined_loading_settles_after_the_timeout() {
+        let timeout = LoadingTimeout::HalfSecond.ticks().unwrap();
+        // stuck fully faded out, stuck half way, and stuck fully faded in
+        for (fade, settled) in [(2.0, Some(true)), (1.0, Some(true)), (0.0, Some(false))] {
+            let mut game = Game { settings: GameSettings { undetermined_loading_timeout: LoadingTimeout::HalfSecond, ..settings() }, ..new_game() };
+            for tick in 1..=timeout {
+                game.update_fade(Some(fade));
+                let expected = if tick < timeout { game.fade_loading() } else { settled };
+                assert_eq!(game.is_loading(), expected, "fade {} tick {}", fade, tick);
+            }
+            assert_eq!(game.fade_loading(), if fade == 0.0 { Some(false) } else { None });
+
+            // never settled without a timeout
+            game.settings.undetermined_loading_timeout = LoadingTimeout::Never;
+            assert_eq!(game.is_loading(), game.fade_loading());
+        }
+    }
+
+    /// Game fading in on Stage 1 of a new run, playing `survivor` on `difficulty`
+    fn fading_in(settings: GameSettings, survivor: Option<&str>, difficulty: Option<i32>) -> Game {
+        let mut game = Game { settings, ..new_game() };
+        game.game_state.survivor.update(survivor.and_then(|survivor| ArrayString::from(survivor).ok()));
+        game.game_state.difficulty.update(difficulty);
+        game.update_fade(Some(2.0));
+        game.update_run(Some(Address::new(1000)), Some(0), Some(false));
+        enter(&mut game, "golemplains");
+        game.update_fade(Some(0.5));
+        return game;
+    }
+
+    #[test]
+    fn required_survivor_gates_the_start() {
+        let required = || GameSettings { ror2_required_survivor: RequiredSurvivor::Huntress, ..settings() };
+        assert!(fading_in(required(), Some("HuntressBody"), None).start());
+        assert!(!fading_in(required(), Some("CommandoBody"), None).start());
+        // unknown before a survivor is picked
+        assert!(fading_in(required(), None, None).start());
+        assert!(fading_in(settings(), Some("CommandoBody"), None).start());
+    }
+
+    #[test]
+    fn required_difficulty_gates_the_start() {
+        let required = |ror2_require_difficulty| GameSettings { ror2_require_difficulty, ..settings() };
+        assert!(fading_in(required(RequiredDifficulty::Monsoon), None, Some(2)).start());
+        assert!(!fading_in(required(RequiredDifficulty::Monsoon), None, Some(1)).start());
+        assert!(!fading_in(required(RequiredDifficulty::Monsoon), None, Some(3)).start());
+        // every Eclipse level
+        assert!(fading_in(required(RequiredDifficulty::Eclipse), None, Some(3)).start());
+        assert!(fading_in(required(RequiredDifficulty::Eclipse), None, Some(10)).start());
+        assert!(!fading_in(required(RequiredDifficulty::Eclipse), None, Some(2)).start());
+        assert!(fading_in(required(RequiredDifficulty::Any), None, Some(0)).start());
+    }
+
+    #[test]
+    fn route_item_splits_once_on_the_first_pickup() {
+        let mut game = in_run(GameSettings { split_on_item: true, ..settings() }, "goolake", 1);
+        // picked up, stacked, lost to a Cleansing Pool, then picked up again
+        for (count, split) in [(0, false), (1, true), (1, false), (2, false), (0, false), (1, false)] {
+            game.game_state.route_item.update(Some(count));
+            game.game_state.route_item_acquired = first_pickup(&game.game_state.route_item, true, &mut game.game_state.route_item_held);
+            assert_eq!(game.split(), split, "{} stacks", count);
+        }
+    }
+
+    /// Kills a boss group on each of the next `kills` pairs of ticks
+    fn kill_bosses(game: &mut Game, kills: i32) {
+        for _ in 0..kills {
+            game.game_state.boss_groups.update(Some(1));
+            game.update_scene_progress();
+            game.game_state.boss_groups.update(Some(0));
+            game.update_scene_progress();
+        }
+    }
+
+    #[test]
+    fn voidling_kill_splits_or_completes_in_the_planetarium_only() {
+        let outcomes = [(VoidlingKill::ResultsScreen, false, false), (VoidlingKill::Split, true, false), (VoidlingKill::Complete, false, true)];
+        for (ror2_voidling_kill, split, completed) in outcomes {
+            let mut game = in_run(GameSettings { ror2_voidling_kill, ..settings() }, "voidraid", 5);
+            kill_bosses(&mut game, VOIDLING_PHASES - 1);
+            assert!(!game.split());
+            assert!(!game.completed());
+            kill_bosses(&mut game, 1);
+            assert_eq!(game.split(), split);
+            assert_eq!(game.completed(), completed);
+        }
+
+        // bosses killed before obliterating are never Voidling
+        let mut game = in_run(GameSettings { ror2_voidling_kill: VoidlingKill::Complete, ..settings() }, "limbo", 5);
+        kill_bosses(&mut game, VOIDLING_PHASES);
+        assert!(!game.voidling_killed());
+        assert!(!game.completed());
+    }
+
+    #[test]
+    fn every_mountain_shrine_splits_once() {
+        let mut game = in_run(GameSettings { ror2_mountain_shrines: true, ..settings() }, "goolake", 1);
+        for (shrines, split) in [(0, false), (1, true), (1, false), (2, true)] {
+            game.game_state.mountain_shrines.update(Some(shrines));
+            assert_eq!(game.split(), split, "{} shrines", shrines);
+        }
+        // the next stage has a new teleporter
+        game.game_state.mountain_shrines.update(None);
+        enter(&mut game, "frozenwall");
+        game.game_state.mountain_shrines.update(Some(0));
+        enter(&mut game, "frozenwall");
+        assert!(!game.split());
+        game.game_state.mountain_shrines.update(Some(1));
+        assert!(game.split());
+    }
+
+    #[test]
+    fn menu_resets_follow_their_toggles() {
+        for enabled in [true, false] {
+            let toggles = || GameSettings { reset_on_lobby: enabled, reset_on_crystalworld: enabled, reset_on_eclipseworld: enabled, ..settings() };
+            for scene in ["lobby", "crystalworld", "eclipseworld"] {
+                let mut game = Game { settings: toggles(), ..new_game() };
+                enter(&mut game, scene);
+                assert_eq!(game.reset(), enabled, "{}", scene);
+            }
+            // the title screen always resets
+            let mut game = Game { settings: toggles(), ..new_game() };
+            enter(&mut game, "title");
+            assert!(game.reset());
+        }
+    }
+
+    #[test]
+    fn multiplayer_alt_endings_need_the_local_player_alive() {
+        let outcomes = [(Some(1), Some(true), true), (Some(3), Some(false), true), (Some(3), Some(true), false), (None, None, true)];
+        for (player_count, local_player_dead, completed) in outcomes {
+            let mut game = in_run(settings(), "voidraid", 5);
+            game.game_state.player_count.update(player_count);
+            game.game_state.local_player_dead.update(local_player_dead);
+            game.update_run(Some(Address::new(1000)), Some(5), Some(true));
+            assert_eq!(game.completed(), completed, "{:?} players, dead {:?}", player_count, local_player_dead);
+        }
+    }
+
+    #[test]
+    fn every_gold_milestone_splits_once_per_run() {
+        let mut game = in_run(GameSettings { split_on_gold: GoldMilestone::OneThousand, ..settings() }, "goolake", 1);
+        // gold held when the watcher becomes valid, earned, spent, and earned again
+        for (gold, split) in [(1_500, false), (1_900, false), (2_100, true), (400, false), (2_500, false), (3_000, true), (5_200, true)] {
+            game.game_state.gold.update(Some(gold));
+            game.update_gold_milestones();
+            assert_eq!(game.split(), split, "{} gold", gold);
+        }
+        assert_eq!(game.game_state.gold_milestones, 5);
+
+        // the next run starts over
+        game.game_state.gold.update(None);
+        game.update_run(None, None, None);
+        game.update_gold_milestones();
+        assert_eq!(game.game_state.gold_milestones, 0);
+        game.update_run(Some(Address::new(2000)), Some(0), Some(false));
+        game.game_state.gold.update(Some(0));
+        game.update_gold_milestones();
+        game.game_state.gold.update(Some(1_000));
+        game.update_gold_milestones();
+        assert!(game.split());
+    }
+
+    #[test]
+    fn first_red_item_splits_once_per_run() {
+        let mut game = in_run(GameSettings { ror2_first_red: true, ..settings() }, "goolake", 1);
+        for run in 0..2 {
+            for (red_items, split) in [(0, false), (1, true), (2, false), (0, false), (1, false)] {
+                game.game_state.red_items.update(Some(red_items));
+                game.game_state.first_red_acquired = first_pickup(&game.game_state.red_items, true, &mut game.game_state.red_item_held);
+                assert_eq!(game.split(), split, "run {}, {} red items", run, red_items);
+            }
+            // outside of a run
+            game.game_state.red_items.update(None);
+            game.game_state.first_red_acquired = first_pickup(&game.game_state.red_items, false, &mut game.game_state.red_item_held);
+            assert!(!game.split());
+        }
+    }
+
+    #[test]
+    fn fifteen_stages_split_once_per_loop() {
+        let mut game = Game { settings: GameSettings { ror2_stages: false, ror2_loop_splits: true, ..settings() }, ..new_game() };
+        golden::replay(&mut game, "
+            scene=golemplains fade=2 run=1000 stage_count=0 results=false
+            fade=0.5 => start
+            scene=goolake stage_count=1
+            scene=frozenwall stage_count=2
+            scene=dampcavesimple stage_count=3
+            scene=skymeadow stage_count=4
+            scene=blackbeach stage_count=5 => split     # first loop
+            scene=foggyswamp stage_count=6
+            scene=wispgraveyard stage_count=7
+            scene=shipgraveyard stage_count=8
+            scene=helminthroost stage_count=9
+            scene=snowyforest stage_count=10 => split   # second loop
+            scene=ancientloft stage_count=11
+            scene=sulfurpools stage_count=12
+            scene=rootjungle stage_count=13
+            scene=skymeadow stage_count=14
+        ");
+        assert_eq!(game.game_state.loop_count, 2);
+    }
+
+    #[test]
+    fn settings_schema_matches_game_settings() {
+        game::assert_schema_matches!(SETTINGS_SCHEMA, settings(), {
+            _ror2_settings, category_presets, ror2_stages, unique_stage_splits, ror2_loop_splits, arm_start, ror2_start_on_run,
+            ror2_required_survivor, ror2_require_difficulty, fade_start_tolerance, undetermined_loading_timeout,
+            ror2_fixed_time_sync, reset_on_lobby, reset_on_crystalworld, reset_on_eclipseworld, reset_on_infinitetowerworld,
+            reset_on_death, pause_on_results, commencement_enter_split, voidraid_enter_split, ror2_voidling_kill, bazaar_enter,
+            bazaar_exit, bazaar_purchase_splits, equipment_use_splits, ror2_first_red, split_on_item, split_item, arena_enter,
+            arena_exit, void_fields_complete_split, ror2_arena_cells, goldshores_enter, goldshores_exit, artifactworld_enter,
+            artifactworld_exit, artifactworld_on_success, obliterate_initiate_split, any_boss_splits, ror2_teleporter,
+            ror2_mithrix_phases, ror2_simulacrum_waves, split_on_teleporter_count, split_on_gold, teleporter_pause_splits,
+            ror2_mountain_shrines, _ror2_stage_splits, split_golemplains, split_blackbeach, split_snowyforest, split_lakes,
+            split_village, split_goolake, split_foggyswamp, split_ancientloft, split_lemuriantemple, split_frozenwall,
+            split_wispgraveyard, split_sulfurpools, split_habitat, split_dampcavesimple, split_shipgraveyard, split_rootjungle,
+            split_skymeadow, split_helminthroost, split_meridian,
+        });
     }
 }
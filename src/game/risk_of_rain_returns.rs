@@ -1,21 +1,93 @@
-use asr::{future::{next_tick, retry}, Process, settings::{Gui, gui::Title}, watcher::Watcher};
+use asr::{future::next_tick, PointerSize, Process, settings::{Gui, gui::Title}, string::ArrayString, time::Duration, watcher::Watcher};
 use async_trait::async_trait;
 use derive;
 
-use crate::game;
+use crate::game::{self, GameAutoSplitter};
 use crate::AutoSplitter;
 
 use version_details::*;
 
 const TARGET_PROCESS_NAME : &str = "Risk of Rain Returns.exe";
 
+/// Ticks without in-game time progress before gameplay counts as loading
+const IGT_STALL_TICKS : u32 = 12;
+
+/// Largest plausible in-game time step in seconds between two ticks
+const MAX_IGT_STEP : f64 = 1.0;
+
+pub const INFO: game::GameInfo = game::GameInfo {
+    id: game::GameId::RiskOfRainReturns,
+    display_name: "Risk of Rain Returns",
+    process_names: &[TARGET_PROCESS_NAME],
+    settings: SETTINGS_SCHEMA,
+};
+
 #[derive(Gui)]
 pub struct GameSettings {
     /// Risk of Rain Returns Settings
     pub _rorr_settings: Title,
     /// Split on stage transitions
+    ///
+    /// Only splits when the stage changes, see the per-stage toggles below
     #[default = false]
     pub rorr_stages: bool,
+    /// Split when leaving Stage 1
+    ///
+    /// Stages are counted from the start of the run, later loops use the toggle of the same stage in the loop
+    #[default = true]
+    pub rorr_split_stage_1: bool,
+    /// Split when leaving Stage 2
+    #[default = true]
+    pub rorr_split_stage_2: bool,
+    /// Split when leaving Stage 3
+    #[default = true]
+    pub rorr_split_stage_3: bool,
+    /// Split when leaving Stage 4
+    #[default = true]
+    pub rorr_split_stage_4: bool,
+    /// Split when leaving Stage 5
+    #[default = true]
+    pub rorr_split_stage_5: bool,
+    /// Timing method
+    ///
+    /// In-Game Time overrides the load removal approximation below
+    pub rorr_timing: Timing,
+    /// Approximate load removal by pausing game time while the in-game timer stalls
+    ///
+    /// This also pauses game time in the pause menu, since the in-game timer stops there as well
+    #[default = false]
+    pub rorr_igt_load_removal: bool,
+    /// Practice only: split on every room change
+    ///
+    /// Includes menus and the lobby, for individual level practice. Do not use for full runs
+    #[default = false]
+    pub rorr_split_every_room: bool,
+}
+
+/// Settings metadata for external configurators, keep in sync with `GameSettings`
+pub const SETTINGS_SCHEMA: &[game::SettingSchema] = &[
+    game::SettingSchema::bool("rorr_stages", "Split on stage transitions", false),
+    game::SettingSchema::bool("rorr_split_stage_1", "Split when leaving Stage 1", true),
+    game::SettingSchema::bool("rorr_split_stage_2", "Split when leaving Stage 2", true),
+    game::SettingSchema::bool("rorr_split_stage_3", "Split when leaving Stage 3", true),
+    game::SettingSchema::bool("rorr_split_stage_4", "Split when leaving Stage 4", true),
+    game::SettingSchema::bool("rorr_split_stage_5", "Split when leaving Stage 5", true),
+    game::SettingSchema::choice("rorr_timing", "Timing method", "RealTime", &["RealTime", "InGameTime"]),
+    game::SettingSchema::bool("rorr_igt_load_removal", "Approximate load removal by pausing game time while the in-game timer stalls", false),
+    game::SettingSchema::bool("rorr_split_every_room", "Practice only: split on every room change", false),
+];
+
+#[derive(Gui, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum Timing {
+    /// Real Time
+    #[default]
+    RealTime,
+    /// In-Game Time (Time Alive)
+    InGameTime,
+}
+
+impl game::Choice for Timing {
+    const OPTIONS: &'static [Self] = &[Self::RealTime, Self::InGameTime];
 }
 
 /// Game state watchers
@@ -25,25 +97,142 @@ pub struct GameVars {
     pub room: Watcher<i32>,
     /// Time Alive
     pub in_game_time: Watcher<f64>,
+    /// Last plausible Time Alive
+    ///
+    /// Garbage reads are skipped, restarting from about 0 where a run can start is a new run and re-anchors the time.
+    pub last_in_game_time: Option<f64>,
+    /// Consecutive ticks the in-game time has not advanced
+    ///
+    /// The game updates slower than the autosplitter, so a single unchanged tick is not a stall.
+    pub igt_stall_ticks: u32,
+    /// Stages entered this run, read from the game
+    ///
+    /// Only available for versions where it has been located
+    pub stage_count: Watcher<i32>,
+    /// Stage rooms entered this run, cleared in menus and the lobby
+    pub stages_entered: i32,
+    /// Last room counted in `stages_entered`, see `update_stage`
+    pub stage_room: Option<i32>,
+    /// Stage number of the run, from `stage_count` for versions where it has been located, otherwise `stages_entered`
+    ///
+    /// Only valid in stage rooms
+    pub stage: Watcher<i32>,
+    /// A stage was counted since the last menu or lobby, cleared on the outro cutscene
+    pub in_stage: bool,
+    /// Entered the outro cutscene from a stage this tick
+    ///
+    /// Also set when the room change itself was not read (e.g. an invalid read in between), but only once per outro.
+    pub outro_entered: bool,
 }
 
 pub struct Game {
     pub settings: GameSettings,
     pub game_state: GameVars,
+    /// The attached version has a stage count offset, see `GameVars::stage`
+    stage_count_located: bool,
+    diagnostics: game::Diagnostics,
 }
 
 impl Game {
-    pub fn new() -> Self { Self { settings: GameSettings::register(), game_state: GameVars::default() } }
+    pub fn new() -> Self { Self { settings: GameSettings::register(), game_state: GameVars::default(), stage_count_located: false, diagnostics: game::Diagnostics::default() } }
 
     fn reset_state(&mut self) {
         self.game_state = GameVars::default();
     }
+
+    /// Per-stage toggle for leaving `stage`, the same toggles repeat every loop
+    fn stage_split_enabled(&self, stage: i32) -> bool {
+        return match (stage - 1).rem_euclid(STAGES_PER_LOOP) + 1 {
+            1 => self.settings.rorr_split_stage_1,
+            2 => self.settings.rorr_split_stage_2,
+            3 => self.settings.rorr_split_stage_3,
+            4 => self.settings.rorr_split_stage_4,
+            _ => self.settings.rorr_split_stage_5,
+        };
+    }
+
+    /// Accepts the current in-game time when it is consistent with either the last accepted time or the previous read
+    ///
+    /// Dropping back down is only a new run when the time restarts from about 0 where a run can start:
+    /// in a menu, the lobby, or the first stage of the run
+    fn update_last_in_game_time(&mut self) {
+        let Some(igt) = self.game_state.in_game_time.pair else { return; };
+        if !igt.current.is_finite() || igt.current < 0.0 {
+            return;
+        }
+
+        let at_run_start = match self.game_state.room.pair.map(|room| room_kind(room.current)) {
+            Some(RoomKind::Menu | RoomKind::Lobby) => true,
+            Some(RoomKind::Unlisted) => self.game_state.stages_entered <= 1,
+            _ => false,
+        };
+        let plausible = match self.game_state.last_in_game_time {
+            // new run
+            Some(last) if igt.current <= last => at_run_start && igt.current <= MAX_IGT_STEP,
+            Some(last) if igt.current - last <= MAX_IGT_STEP => true,
+            // consecutive reads agree, recovers from lag spikes
+            _ => (0.0..=MAX_IGT_STEP).contains(&(igt.current - igt.old)),
+        };
+        if plausible {
+            self.game_state.last_in_game_time = Some(igt.current);
+        }
+    }
+
+    /// Updates the outro, stage, and in-game time stall tracking from this tick's room and Time Alive
+    ///
+    /// An unlisted room only counts as a stage once Time Alive runs in it, so a cutscene or menu room missing
+    /// from `RoomKind` never shifts the stage numbering. Until then the stage of the previous room is kept
+    fn update_stage(&mut self) {
+        self.game_state.outro_entered = false;
+        let room = self.game_state.room.pair.map(|room| room.current);
+        match room.map(room_kind) {
+            Some(RoomKind::Outro) => {
+                self.game_state.outro_entered = self.game_state.in_stage;
+                self.game_state.in_stage = false;
+            },
+            Some(RoomKind::Menu | RoomKind::Lobby) => {
+                self.game_state.in_stage = false;
+                self.game_state.stages_entered = 0;
+                self.game_state.stage_room = None;
+            },
+            Some(RoomKind::Unlisted) => {
+                let time_alive_runs = self.game_state.in_game_time.pair.is_some_and(|in_game_time| in_game_time.increased());
+                if time_alive_runs && self.game_state.stage_room != room {
+                    self.game_state.stages_entered += 1;
+                    self.game_state.stage_room = room;
+                }
+                self.game_state.in_stage = self.game_state.stage_room.is_some();
+            },
+            None => {},
+        }
+        let stage = match room.map(room_kind) {
+            Some(RoomKind::Unlisted) if self.game_state.stages_entered == 0 => None,
+            Some(RoomKind::Unlisted) if self.stage_count_located => self.game_state.stage_count.pair.map(|stage_count| stage_count.current),
+            Some(RoomKind::Unlisted) => Some(self.game_state.stages_entered),
+            _ => None,
+        };
+        self.game_state.stage.update(stage);
+        match self.game_state.in_game_time.pair {
+            Some(in_game_time) if !in_game_time.increased() => { self.game_state.igt_stall_ticks += 1; },
+            _ => { self.game_state.igt_stall_ticks = 0; }
+        }
+    }
 }
 
+
+// room ids: see `RoomKind`
+//
+// There are no dedicated boss arena rooms: teleporter bosses spawn in the stage room itself,
+// so boss fights can not be split on by room ID alone.
+
 #[async_trait]
 impl game::GameAutoSplitter for Game {
+    fn display_name(&self) -> &'static str { return INFO.display_name; }
+
+    fn id(&self) -> game::GameId { return INFO.id; }
+
     /// "Risk of Rain Returns.exe"
-    fn process_names(&self) -> &[&str] { return &[TARGET_PROCESS_NAME]; }
+    fn process_names(&self) -> &[&str] { return INFO.process_names; }
 
     fn register_settings(&mut self) {
         self.settings = GameSettings::register();
@@ -52,10 +241,35 @@ impl game::GameAutoSplitter for Game {
     async fn attached(&mut self, process: &Process, autosplitter: &mut AutoSplitter) {
         self.reset_state();
 
-        let (main_module, _main_module_size) = process.wait_module_range(&TARGET_PROCESS_NAME).await; // slow, but avoids deadlock
+        let (main_module, main_module_size) = process.wait_module_range(&TARGET_PROCESS_NAME).await; // slow, but avoids deadlock
 
         // game version detection and handling
-        let (room, in_game_time) = retry(|| find_gamevar_pointers(process, &main_module)).await; // intentionally hangs for unsupported versions
+        let mut reported = false;
+        let (room, in_game_time, stage_count) = loop {
+            if let Some(pointers) = find_gamevar_pointers(process, &main_module, main_module_size) {
+                break pointers;
+            }
+            // report once so users know why nothing happens, and can submit the build string of their version
+            if !reported {
+                report_unknown_version(process, &main_module, main_module_size, autosplitter.debug());
+                reported = true;
+            }
+            // unsupported versions keep polling until the game closes, the timer is handled as if no game was attached
+            autosplitter.update_loop(None);
+            next_tick().await;
+        };
+
+        let (version, paths) = version_diagnostics(process, &main_module, main_module_size).unzip();
+        self.diagnostics = game::Diagnostics {
+            process_name: Some(TARGET_PROCESS_NAME),
+            module: Some((main_module, main_module_size)),
+            version,
+            paths: paths.unwrap_or_default(),
+        };
+
+        self.stage_count_located = stage_count.is_some();
+        let mut room_var = game::TimerVariable::new("Room");
+        let mut stage_count_var = game::TimerVariable::new("Stage Count");
 
         loop {
             // update game state watchers
@@ -66,11 +280,20 @@ impl game::GameAutoSplitter for Game {
                 }
             );
             self.game_state.in_game_time.update(
-                match in_game_time.deref::<f64>(&process) {
-                    Ok(val) => Some(val),
-                    _ => None
-                }
+                in_game_time.as_ref().and_then(|in_game_time| in_game_time.deref::<f64>(&process).ok())
+            );
+            self.game_state.stage_count.update(
+                stage_count.as_ref().and_then(|stage_count| stage_count.deref::<i32>(&process).ok())
             );
+            self.update_last_in_game_time();
+            self.update_stage();
+
+            room_var.update(self.game_state.room.pair.filter(|_| autosplitter.debug()).map(|room| room.current));
+            stage_count_var.update(self.game_state.stage_count.pair.filter(|_| autosplitter.debug()).map(|stage_count| stage_count.current));
+
+            if autosplitter.diagnostics_requested() {
+                self.dump_diagnostics(process);
+            }
 
             self.settings.update();
             // cede control to main autosplitter logic loop
@@ -82,7 +305,7 @@ impl game::GameAutoSplitter for Game {
     /// Start when entering a game from the lobby
     fn start(&self) -> bool {
         if let Some(room) = self.game_state.room.pair {
-            return room.changed_from(&4) &&
+            return room.changed_from(&LOBBY_ROOM) &&
                 match room.current {
                     2 | 3 | 4 => false,
                     _ => true
@@ -94,161 +317,408 @@ impl game::GameAutoSplitter for Game {
     /// Reset when entering the lobby
     fn reset(&self) -> bool {
         if let Some(room) = self.game_state.room.pair {
-            return room.current == 4;
+            return room.current == LOBBY_ROOM;
         }
         return false;
     }
 
-    /// Split on stage change
+    /// No resets while on or just leaving the outro cutscene
+    fn reset_allowed(&self) -> bool {
+        if let Some(room) = self.game_state.room.pair {
+            return room.old != OUTRO_ROOM && room.current != OUTRO_ROOM;
+        }
+        return true;
+    }
+
+    /// Split on stage change, see `GameVars::stage`
+    ///
+    /// Practice mode splits on every room change instead.
     fn split(&self) -> bool {
-        const MENU_ROOMS : [i32; 5] = [1, 2, 3, 4, 7];
+        if self.settings.rorr_split_every_room {
+            return self.game_state.room.pair.is_some_and(|room| room.changed());
+        }
 
-        // Stage/room changed
-        if let Some(room) = self.game_state.room.pair {
-            if room.changed() {
-                // Don't split when returning to the lobby
-                return self.settings.rorr_stages && !(MENU_ROOMS.contains(&room.old) || MENU_ROOMS.contains(&room.current));
-            }
+        if !self.settings.rorr_stages {
+            return false;
+        }
+
+        // the stage is invalid outside of stage rooms, so entering the first stage or returning to the lobby never splits
+        if let Some(stage) = self.game_state.stage.pair {
+            return stage.increased() && self.stage_split_enabled(stage.old);
         }
         return false;
     }
 
-    /// Completed on reaching the outro cutscene
+    /// Completed on reaching the outro cutscene from a stage
+    ///
+    /// Tracked across invalid reads, so a missed transition into the outro still completes once
     fn completed(&self) -> bool {
+        return self.game_state.outro_entered;
+    }
+
+    /// Loading while the in-game timer stalls during gameplay, when enabled
+    ///
+    /// Undetermined while the in-game timer is invalid
+    fn is_loading(&self) -> Option<bool> {
+        if !self.settings.rorr_igt_load_removal {
+            return Some(false);
+        }
         if let Some(room) = self.game_state.room.pair {
-            if room.changed() && room.current == 8 {
-                return true;
+            if room_kind(room.current) != RoomKind::Unlisted {
+                return Some(false);
             }
         }
-        return false;
+        self.game_state.in_game_time.pair?;
+        return Some(self.game_state.igt_stall_ticks >= IGT_STALL_TICKS);
     }
 
-    /// No load removal
-    fn is_loading(&self) -> Option<bool> { Some(false) }
+    fn soft_reset(&mut self) {
+        self.reset_state();
+    }
+
+    fn dump_diagnostics(&self, process: &Process) {
+        self.diagnostics.print(self.display_name(), process, PointerSize::Bit64);
+    }
+
+    /// Current room, outside of menus and the lobby
+    fn progress(&self) -> Option<ArrayString<32>> {
+        let room = self.game_state.room.pair?.current;
+        if let RoomKind::Menu | RoomKind::Lobby = room_kind(room) {
+            return None;
+        }
+        return game::format_progress("room", room);
+    }
+
+    /// Game time synced to Time Alive, or no load removal unless approximated from in-game timer stalls
+    fn load_strategy(&self) -> game::LoadStrategy {
+        if self.settings.rorr_timing == Timing::InGameTime {
+            return game::LoadStrategy::InGameTime;
+        }
+        if self.settings.rorr_igt_load_removal {
+            return game::LoadStrategy::InGameTimeStall;
+        }
+        return game::LoadStrategy::None;
+    }
+
+    /// Time Alive, guarded against garbage reads and run-to-run resets
+    ///
+    /// Holds the last plausible time while the in-game timer is invalid on room transitions
+    fn game_time(&self) -> Option<Duration> {
+        return self.game_state.last_in_game_time.map(Duration::seconds_f64);
+    }
 
 }
 
 mod version_details {
     use asr::{Address, deep_pointer::DeepPointer, Process};
 
+    use crate::game::{NamedPath, version_table::{BuildString, GameVersionData, VersionTable}};
+
 // public interface
 
+    /// What a room ID is used for
+    ///
+    /// Only the menu, lobby, and outro room IDs are known. Stage room IDs have not been mapped,
+    /// so every other room is `Unlisted` and only counts as a stage once Time Alive runs in it
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    pub enum RoomKind {
+        /// Title screen and the other menus, room IDs 1, 2, 3, and 7
+        Menu,
+        /// Character select, solo and online co-op, room ID 4
+        Lobby,
+        /// Outro cutscene after the final stage, room ID 8
+        Outro,
+        /// Any other room, playable stages and any cutscene not listed above
+        Unlisted,
+    }
+
+    /// Character select lobby
+    pub const LOBBY_ROOM: i32 = 4;
+    /// Outro cutscene
+    pub const OUTRO_ROOM: i32 = 8;
+    /// Regular stages before the run loops back to Stage 1
+    pub const STAGES_PER_LOOP: i32 = 5;
+
+    /// Kind of a room ID
+    ///
+    /// Room IDs are the same in every supported version, move them into `GameVersionData` once a build renumbers its rooms
+    pub fn room_kind(room: i32) -> RoomKind {
+        return match room {
+            LOBBY_ROOM => RoomKind::Lobby,
+            OUTRO_ROOM => RoomKind::Outro,
+            room if MENU_ROOMS.contains(&room) => RoomKind::Menu,
+            _ => RoomKind::Unlisted,
+        };
+    }
+
     /// Guaranteed to be large enough to hold a DeepPointer to "room" from any version
-    pub type RoomPointer = DeepPointer::<{SupportedGameVersions::room_len()}>;
+    pub type RoomPointer = DeepPointer::<{VERSIONS.path_len(GameVar::Room as usize)}>;
     /// Guaranteed to be large enough to hold a DeepPointer to "in_game_time" from any version
-    pub type IGTPointer = DeepPointer::<{SupportedGameVersions::igt_len()}>;
+    pub type IGTPointer = DeepPointer::<{VERSIONS.path_len(GameVar::InGameTime as usize)}>;
+    /// Guaranteed to be large enough to hold a DeepPointer to "stage_count" from any version
+    pub type StageCountPointer = DeepPointer::<{VERSIONS.path_len(GameVar::StageCount as usize)}>;
 
     /// Autodetects game version and locates offsets for game vars
-    pub fn find_gamevar_pointers<'a>(process: &'a Process, module_offset: &'a Address) -> Option<(RoomPointer, IGTPointer)> {
-        for gv in SupportedGameVersions::data() {
-            if check_build_string(process, module_offset, &gv.build_string) {
-                return Some((RoomPointer::new_64bit(*module_offset, gv.offsets.room), IGTPointer::new_64bit(*module_offset, gv.offsets.in_game_time)));
-            }
+    ///
+    /// "in_game_time" and "stage_count" are None for versions where they have not been located
+    pub fn find_gamevar_pointers<'a>(process: &'a Process, module_offset: &'a Address, module_size: u64) -> Option<(RoomPointer, Option<IGTPointer>, Option<StageCountPointer>)> {
+        let gv = VERSIONS.detect(process, module_offset, module_size)?;
+        let room = RoomPointer::new_64bit(*module_offset, gv.offsets[GameVar::Room as usize]?);
+        let in_game_time = gv.offsets[GameVar::InGameTime as usize].map(|path| IGTPointer::new_64bit(*module_offset, path));
+        let stage_count = gv.offsets[GameVar::StageCount as usize].map(|path| StageCountPointer::new_64bit(*module_offset, path));
+        return Some((room, in_game_time, stage_count));
+    }
+
+    /// Detected version name and the pointer path of every game var
+    pub fn version_diagnostics(process: &Process, module_offset: &Address, module_size: u64) -> Option<(&'static str, Vec<NamedPath>)> {
+        let gv = VERSIONS.detect(process, module_offset, module_size)?;
+        return Some((gv.name, gv.named_paths(*module_offset, &GAME_VAR_NAMES)));
+    }
+
+    /// Prints that the game version is unsupported, and with `details` the build strings found in it
+    pub fn report_unknown_version(process: &Process, module_offset: &Address, module_size: u64, details: bool) {
+        let supported: Vec<&str> = VERSIONS.data().iter().map(|gv| gv.name).collect();
+        asr::print_message(&format!("{}: unsupported game version, supported versions are {}", super::INFO.display_name, supported.join(", ")));
+        if details {
+            VERSIONS.report_unknown(process, module_offset, module_size);
+        } else {
+            asr::print_message("Enable diagnostic messages and restart the game to print its build string");
         }
-        return None;
     }
 
 // implementation details
 
-    /// Version specific build info used for version detection
-    struct BuildString {
-        address: u64,
-        expected: &'static str,
-    }
+    const MENU_ROOMS : [i32; 4] = [1, 2, 3, 7];
 
-    /// Version specific pointer offsets to game vars
-    struct GameVarOffsets {
-        pub room: &'static [u64],
-        pub in_game_time: &'static [u64],
+    /// Index of each game var in `GameVersionData::offsets`
+    enum GameVar {
+        Room,
+        InGameTime,
+        StageCount,
     }
 
-    struct GameVersionData {
-        build_string: BuildString,
-        offsets: GameVarOffsets,
+    impl GameVar {
+        const COUNT: usize = 3;
     }
 
+    /// Game var names for diagnostics, in `GameVar` order
+    const GAME_VAR_NAMES: [&str; GameVar::COUNT] = ["room", "in_game_time", "stage_count"];
+
+    const VERSION_DATA: [GameVersionData<{GameVar::COUNT}>; 3] = [
+        { GameVersionData {
+            name: "1.0.3",
+            build_string: Some(BuildString {
+                address: 0x1A7C700,
+                expected: "BUILD_ID: 234, BUILD_BRANCH: PATCH_1_0_3, VERSION_STRING: 1.0.3"
+            }),
+            module_size: None,
+            offsets: [
+                /* room */ Some(&[0x2127B18]),
+                /* in_game_time */ Some(&[0x1F01C98, 0x10, 0x1CF0, 0x1B0, 0x48, 0x10, 0x0, 0x0, 0x48, 0x10, 0x50, 0x0]),
+                /* stage_count */ None,
+            ]
+        } },
+
+        { GameVersionData {
+            name: "1.0.4",
+            build_string: Some(BuildString {
+                address: 0x1ABCB10,
+                expected: "BUILD_ID: 242, BUILD_BRANCH: the-mouse-aim-branch, VERSION_STRING: 1.0.4"
+            }),
+            module_size: None,
+            offsets: [
+                /* room */ Some(&[0x2172888]),
+                /* in_game_time */ Some(&[0x01F5F300, 0x170, 0x10, 0x90, 0x0, 0x48, 0x10, 0x60, 0x0, 0x48, 0x10, 0x1B0, 0x0]),
+                /* stage_count */ None,
+            ]
+        } },
+
+        // v1.0.5 shipped without bumping its VERSION_STRING, the expected string has to match the game's own.
+        // BUILD_ID 248 already tells it apart from v1.0.4 (BUILD_ID 242), and `name` is what debug output shows
+        { GameVersionData {
+            name: "1.0.5",
+            build_string: Some(BuildString {
+                address: 0x1ABC988,
+                expected: "BUILD_ID: 248, BUILD_BRANCH: master, VERSION_STRING: 1.0.4"
+            }),
+            module_size: None,
+            offsets: [
+                /* room */ Some(&[0x21729D8]),
+                /* in_game_time */ Some(&[0x01F5F450, 0x120, 0x10, 0x90, 0x0, 0x48, 0x10, 0xd0, 0x0, 0x48, 0x10, 0x2e0, 0x0]),
+                /* stage_count */ None,
+            ]
+        } },
+    ];
+
     /// Holds static data for each game version the autosplitter supports
-    struct SupportedGameVersions;
-
-    impl SupportedGameVersions {
-        /// Autosplitter reference data for every supported version
-        const fn data() -> &'static [GameVersionData] { return &Self::VERSION_DATA; }
-        /// size of longest BuildString
-        const fn strbuf_len() -> usize { return Self::max_len_all().0; }
-        /// size of longest room pointer path
-        const fn room_len() -> usize { return Self::max_len_all().1; }
-        /// size of longest in_game_time pointer path
-        const fn igt_len() -> usize { return Self::max_len_all().2; }
-
-        const VERSION_DATA: [GameVersionData; 3] = [
-            { GameVersionData {
-                build_string: { BuildString {
-                    address: 0x1A7C700,
-                    expected: "BUILD_ID: 234, BUILD_BRANCH: PATCH_1_0_3, VERSION_STRING: 1.0.3"
-                } },
-                offsets: { GameVarOffsets {
-                    room: &[0x2127B18],
-                    in_game_time: &[0x1F01C98, 0x10, 0x1CF0, 0x1B0, 0x48, 0x10, 0x0, 0x0, 0x48, 0x10, 0x50, 0x0]
-                } }
-            } },
-
-            { GameVersionData {
-                build_string: { BuildString {
-                    address: 0x1ABCB10,
-                    expected: "BUILD_ID: 242, BUILD_BRANCH: the-mouse-aim-branch, VERSION_STRING: 1.0.4"
-                } },
-                offsets: { GameVarOffsets {
-                    room: &[0x2172888],
-                    in_game_time: &[0x01F5F300, 0x170, 0x10, 0x90, 0x0, 0x48, 0x10, 0x60, 0x0, 0x48, 0x10, 0x1B0, 0x0]
-                } }
-            } },
-
-            { GameVersionData {
-                build_string: { BuildString {
-                    address: 0x1ABC988,
-                    expected: "BUILD_ID: 248, BUILD_BRANCH: master, VERSION_STRING: 1.0.4"
-                } },
-                offsets: { GameVarOffsets {
-                    room: &[0x21729D8],
-                    in_game_time: &[0x01F5F450, 0x120, 0x10, 0x90, 0x0, 0x48, 0x10, 0xd0, 0x0, 0x48, 0x10, 0x2e0, 0x0]
-                } }
-            } },
-        ];
-
-        /// utility function for constant evaluation contexts
-        const fn max_len_all() -> (usize, usize, usize) {
-            let mut max_build_str: usize = 0;
-            let mut max_room: usize = 0;
-            let mut max_igt: usize = 0;
-
-            let mut i = 0; while i < Self::VERSION_DATA.len() {
-                let build_str_len = Self::VERSION_DATA[i].build_string.expected.len();
-                let room_len = Self::VERSION_DATA[i].offsets.room.len();
-                let igt_len = Self::VERSION_DATA[i].offsets.in_game_time.len();
-
-                if max_build_str < build_str_len {
-                    max_build_str = build_str_len;
-                }
-                if max_room < room_len {
-                    max_room = room_len;
+    const VERSIONS: VersionTable<{GameVar::COUNT}> = VersionTable::new(&VERSION_DATA);
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// Builds are only told apart by their build string, a repeated one would always detect the earlier entry
+        #[test]
+        fn build_strings_are_unique() {
+            for (i, gv) in VERSION_DATA.iter().enumerate() {
+                let expected = gv.build_string.as_ref().map(|build_string| build_string.expected);
+                assert!(expected.is_some(), "{} has no build string", gv.name);
+                for other in &VERSION_DATA[i + 1..] {
+                    assert!(other.build_string.as_ref().map(|build_string| build_string.expected) != expected, "{} and {} share a build string", gv.name, other.name);
                 }
-                if max_igt < igt_len {
-                    max_igt = igt_len;
-                }
-
-                i += 1;
             }
+        }
 
-            return (max_build_str, max_room, max_igt);
+        /// Release of each known BUILD_ID
+        const RELEASES: [(u32, &str); 3] = [(234, "1.0.3"), (242, "1.0.4"), (248, "1.0.5")];
+
+        /// Each build string is keyed on the BUILD_ID of its declared version
+        ///
+        /// VERSION_STRING has to match the declared version too, except for v1.0.5 which kept 1.0.4's
+        #[test]
+        fn build_strings_contain_their_declared_version() {
+            for gv in VERSION_DATA.iter() {
+                let expected = gv.build_string.as_ref().map_or("", |build_string| build_string.expected);
+                let (build_id, _) = RELEASES.iter().find(|(_, release)| *release == gv.name)
+                    .unwrap_or_else(|| panic!("{} has no known BUILD_ID", gv.name));
+                assert!(expected.starts_with(&format!("BUILD_ID: {},", build_id)), "{} expects {}", gv.name, expected);
+                let version_string = if *build_id == 248 { "1.0.4" } else { gv.name };
+                assert!(expected.ends_with(&format!("VERSION_STRING: {}", version_string)), "{} expects {}", gv.name, expected);
+            }
         }
     }
 
-    fn check_build_string(process: &Process, module_offset: &Address, build_string: &'static BuildString) -> bool {
-        let mut buf: [u8; SupportedGameVersions::strbuf_len()] = [0; SupportedGameVersions::strbuf_len()];
-        if process.read_into_buf(module_offset.add(build_string.address), &mut buf).is_ok() {
-            return buf[0..build_string.expected.len()].iter().zip(build_string.expected.as_bytes().iter()).all(|(a,b)| a == b);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::autosplitter::golden::{self, Reads, Replay};
+
+    fn settings() -> GameSettings {
+        return GameSettings {
+            _rorr_settings: Title,
+            rorr_stages: true,
+            rorr_split_stage_1: true,
+            rorr_split_stage_2: true,
+            rorr_split_stage_3: true,
+            rorr_split_stage_4: true,
+            rorr_split_stage_5: true,
+            rorr_timing: Timing::InGameTime,
+            rorr_igt_load_removal: false,
+            rorr_split_every_room: false,
+        };
+    }
+
+    fn new_game() -> Game {
+        return Game { settings: settings(), game_state: GameVars::default(), stage_count_located: false, diagnostics: game::Diagnostics::default() };
+    }
+
+    /// Feeds one tick of room and Time Alive reads, in the order `attached` updates them
+    fn tick(game: &mut Game, room: i32, in_game_time: f64) {
+        game.game_state.room.update(Some(room));
+        game.game_state.in_game_time.update(Some(in_game_time));
+        game.update_last_in_game_time();
+        game.update_stage();
+    }
+
+    impl Replay for Game {
+        fn replay_tick(&mut self, reads: &Reads) {
+            self.game_state.room.update(reads.get("room"));
+            self.game_state.in_game_time.update(reads.get("in_game_time"));
+            self.game_state.stage_count.update(reads.get("stage_count"));
+            self.update_last_in_game_time();
+            self.update_stage();
         }
-        return false;
     }
 
+    /// A stage room, any unlisted room ID that Time Alive runs in
+    const STAGE_ROOM: i32 = 20;
+    const OTHER_STAGE_ROOM: i32 = 21;
+
+    #[test]
+    fn in_game_time_follows_a_running_timer() {
+        let mut game = new_game();
+        tick(&mut game, STAGE_ROOM, 10.0);
+        tick(&mut game, STAGE_ROOM, 10.5);
+        assert_eq!(game.game_state.last_in_game_time, Some(10.5));
+    }
+
+    #[test]
+    fn in_game_time_skips_garbage_reads() {
+        let mut game = new_game();
+        tick(&mut game, STAGE_ROOM, 10.0);
+        tick(&mut game, STAGE_ROOM, 5000.0);
+        tick(&mut game, STAGE_ROOM, f64::NAN);
+        tick(&mut game, STAGE_ROOM, -1.0);
+        assert_eq!(game.game_state.last_in_game_time, Some(10.0));
+    }
+
+    #[test]
+    fn in_game_time_ignores_drops_in_the_middle_of_a_run() {
+        let mut game = new_game();
+        tick(&mut game, STAGE_ROOM, 10.0);
+        tick(&mut game, OTHER_STAGE_ROOM, 10.5);
+        tick(&mut game, STAGE_ROOM, 11.0);
+        // a drop to 0 on a later stage is a bad read, not a new run
+        tick(&mut game, STAGE_ROOM, 0.0);
+        assert_eq!(game.game_state.last_in_game_time, Some(11.0));
+        // as is a drop that doesn't restart from 0
+        tick(&mut game, LOBBY_ROOM, 5.0);
+        assert_eq!(game.game_state.last_in_game_time, Some(11.0));
+    }
+
+    #[test]
+    fn in_game_time_restarts_from_zero_in_the_lobby_or_first_stage() {
+        let mut game = new_game();
+        tick(&mut game, STAGE_ROOM, 10.0);
+        tick(&mut game, OTHER_STAGE_ROOM, 10.5);
+        tick(&mut game, LOBBY_ROOM, 0.0);
+        assert_eq!(game.game_state.last_in_game_time, Some(0.0));
+
+        let mut game = new_game();
+        tick(&mut game, STAGE_ROOM, 120.0);
+        tick(&mut game, STAGE_ROOM, 0.2);
+        assert_eq!(game.game_state.last_in_game_time, Some(0.2));
+    }
+
+    #[test]
+    fn stages_are_counted_once_time_alive_runs() {
+        let mut game = new_game();
+        tick(&mut game, LOBBY_ROOM, 0.0);
+        tick(&mut game, STAGE_ROOM, 0.0);
+        assert!(game.game_state.stage.pair.is_none());
+        tick(&mut game, STAGE_ROOM, 0.5);
+        assert_eq!(game.game_state.stage.pair.map(|stage| stage.current), Some(1));
+
+        // an unlisted cutscene between stages keeps the stage, Time Alive doesn't run in it
+        tick(&mut game, 9, 0.5);
+        tick(&mut game, 9, 0.5);
+        assert_eq!(game.game_state.stage.pair.map(|stage| stage.current), Some(1));
+        assert!(!game.split());
+        tick(&mut game, OTHER_STAGE_ROOM, 1.0);
+        assert_eq!(game.game_state.stage.pair.map(|stage| stage.current), Some(2));
+        assert!(game.split());
+        assert!(game.game_state.in_stage);
+    }
+
+    #[test]
+    fn golden_run() {
+        golden::replay(&mut new_game(), include_str!("golden/rorr.golden"));
+    }
+
+    #[test]
+    fn stage_count_splits_like_stages_entered() {
+        let mut game = new_game();
+        game.stage_count_located = true;
+        golden::replay(&mut game, include_str!("golden/rorr.golden"));
+    }
+
+    #[test]
+    fn settings_schema_matches_game_settings() {
+        game::assert_schema_matches!(SETTINGS_SCHEMA, settings(), {
+            _rorr_settings, rorr_stages, rorr_split_stage_1, rorr_split_stage_2, rorr_split_stage_3, rorr_split_stage_4,
+            rorr_split_stage_5, rorr_timing, rorr_igt_load_removal, rorr_split_every_room,
+        });
+    }
 }
@@ -0,0 +1,258 @@
+use asr::{Address, Process};
+
+/// Start of every known build string
+const BUILD_ID_PREFIX: &str = "BUILD_ID:";
+
+/// Bytes read at a time when scanning the module for build strings
+const SCAN_CHUNK_LEN: usize = 0x1000;
+
+/// Pointer path to a game var
+///
+/// None when the game var has not been located for a version
+pub type OffsetPath = Option<&'static [u64]>;
+
+/// Version specific build info used for version detection
+pub struct BuildString {
+    pub address: u64,
+    pub expected: &'static str,
+}
+
+/// Autosplitter reference data for a single game version
+///
+/// `VARS` is the number of game vars, `offsets` is indexed by the game's own game var enum
+pub struct GameVersionData<const VARS: usize> {
+    /// Human readable version name, for debug output
+    pub name: &'static str,
+    /// None always matches, only use it for a fallback entry at the end of a table
+    pub build_string: Option<BuildString>,
+    /// Size of the main module, None matches any size
+    ///
+    /// Tells apart builds with the same (or no) build string, like the Windows and Linux/Proton modules of a version
+    pub module_size: Option<u64>,
+    /// Version specific pointer offsets to game vars
+    pub offsets: [OffsetPath; VARS],
+}
+
+impl<const VARS: usize> GameVersionData<VARS> {
+    /// Pointer paths of every game var, `names` is in the same order as `offsets`
+    pub fn named_paths(&self, module_offset: Address, names: &[&'static str; VARS]) -> Vec<crate::game::NamedPath> {
+        return names.iter().zip(self.offsets.iter())
+            .map(|(&name, path)| (name, path.map(|path| (module_offset, path.to_vec()))))
+            .collect();
+    }
+}
+
+/// Holds static data for each game version the autosplitter supports
+pub struct VersionTable<const VARS: usize> {
+    versions: &'static [GameVersionData<VARS>],
+}
+
+impl<const VARS: usize> VersionTable<VARS> {
+    pub const fn new(versions: &'static [GameVersionData<VARS>]) -> Self { Self { versions } }
+
+    /// Autosplitter reference data for every supported version
+    pub const fn data(&self) -> &'static [GameVersionData<VARS>] { return self.versions; }
+
+    /// size of longest BuildString
+    pub const fn strbuf_len(&self) -> usize {
+        let mut max_len: usize = 0;
+
+        let mut i = 0; while i < self.versions.len() {
+            if let Some(build_string) = &self.versions[i].build_string {
+                if max_len < build_string.expected.len() {
+                    max_len = build_string.expected.len();
+                }
+            }
+            i += 1;
+        }
+
+        return max_len;
+    }
+
+    /// size of longest pointer path for the game var at index `var`
+    ///
+    /// Use this to size DeepPointers so they can hold the path from any version
+    pub const fn path_len(&self, var: usize) -> usize {
+        let mut max_len: usize = 0;
+
+        let mut i = 0; while i < self.versions.len() {
+            if let Some(path) = self.versions[i].offsets[var] {
+                if max_len < path.len() {
+                    max_len = path.len();
+                }
+            }
+            i += 1;
+        }
+
+        return max_len;
+    }
+
+    /// Autodetects game version by checking each module size and build string in order
+    pub fn detect(&self, process: &Process, module_offset: &Address, module_size: u64) -> Option<&'static GameVersionData<VARS>> {
+        let mut buf = vec![0u8; self.strbuf_len()];
+        return self.versions.iter()
+            .filter(|gv| gv.module_size.map_or(true, |size| size == module_size))
+            .find(|gv| match &gv.build_string {
+                Some(build_string) => check_build_string(process, module_offset, build_string, &mut buf),
+                None => true,
+            });
+    }
+
+    /// Prints whatever is found at each known build string address, and any `BUILD_ID:` string in the module
+    ///
+    /// Use this when `detect()` fails, the output is what a new `GameVersionData` entry needs
+    pub fn report_unknown(&self, process: &Process, module_offset: &Address, module_size: u64) {
+        asr::print_message("Unknown game version, build string candidates:");
+        for build_string in self.versions.iter().filter_map(|gv| gv.build_string.as_ref()) {
+            let found = read_string(process, module_offset.add(build_string.address), build_string.expected.len());
+            asr::print_message(&format!("  0x{:X}: {}", build_string.address, found));
+        }
+        match find_in_module(process, module_offset, module_size, BUILD_ID_PREFIX.as_bytes()) {
+            Some(offset) => {
+                let found = read_string(process, module_offset.add(offset), self.strbuf_len().max(BUILD_ID_PREFIX.len()) * 2);
+                asr::print_message(&format!("  0x{:X}: {}", offset, found));
+            },
+            None => asr::print_message(&format!("  no \"{}\" string found", BUILD_ID_PREFIX)),
+        }
+    }
+}
+
+/// Reads up to `len` bytes as text, stopping at the first NUL
+fn read_string(process: &Process, address: Address, len: usize) -> String {
+    let mut buf = vec![0u8; len];
+    if process.read_into_buf(address, &mut buf).is_err() {
+        return String::from("<unreadable>");
+    }
+    return text_until_nul(&buf);
+}
+
+/// Bytes up to the first NUL as text
+fn text_until_nul(buf: &[u8]) -> String {
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    return String::from_utf8_lossy(&buf[..end]).into_owned();
+}
+
+/// Module offset of the first occurrence of `needle`
+fn find_in_module(process: &Process, module_offset: &Address, module_size: u64, needle: &[u8]) -> Option<u64> {
+    return scan_chunks(module_size, needle, |offset, chunk| process.read_into_buf(module_offset.add(offset), chunk).is_ok());
+}
+
+/// Offset of the first occurrence of `needle` in `size` bytes, read a chunk at a time
+///
+/// `read` fills the chunk at an offset and returns false when it is unreadable, unreadable chunks are skipped.
+/// Chunks overlap so matches crossing a chunk boundary are still found
+fn scan_chunks(size: u64, needle: &[u8], mut read: impl FnMut(u64, &mut [u8]) -> bool) -> Option<u64> {
+    let mut buf = vec![0u8; SCAN_CHUNK_LEN];
+    let step = (SCAN_CHUNK_LEN - needle.len() + 1) as u64;
+    let mut offset: u64 = 0;
+    while offset < size {
+        let len = (size - offset).min(SCAN_CHUNK_LEN as u64) as usize;
+        let chunk = &mut buf[..len];
+        if read(offset, chunk) {
+            if let Some(pos) = find_bytes(chunk, needle) {
+                return Some(offset + pos as u64);
+            }
+        }
+        offset += step;
+    }
+    return None;
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    return haystack.windows(needle.len()).position(|window| window == needle);
+}
+
+fn check_build_string(process: &Process, module_offset: &Address, build_string: &BuildString, buf: &mut [u8]) -> bool {
+    let buf = &mut buf[0..build_string.expected.len()];
+    if process.read_into_buf(module_offset.add(build_string.address), buf).is_ok() {
+        return buf.iter().zip(build_string.expected.as_bytes().iter()).all(|(a,b)| a == b);
+    }
+    return false;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERSION_DATA: [GameVersionData<2>; 3] = [
+        GameVersionData {
+            name: "1.1",
+            build_string: Some(BuildString { address: 0x100, expected: "BUILD_ID: 12" }),
+            module_size: None,
+            offsets: [Some(&[0x10, 0x20]), None],
+        },
+        GameVersionData {
+            name: "1.0",
+            build_string: Some(BuildString { address: 0x200, expected: "BUILD_ID: 9, VERSION_STRING: 1.0" }),
+            module_size: None,
+            offsets: [Some(&[0x10]), Some(&[0x8, 0x10, 0x18])],
+        },
+        GameVersionData {
+            name: "fallback",
+            build_string: None,
+            module_size: None,
+            offsets: [None, None],
+        },
+    ];
+
+    const VERSIONS: VersionTable<2> = VersionTable::new(&VERSION_DATA);
+
+    #[test]
+    fn buffers_fit_every_version() {
+        assert_eq!(VERSIONS.strbuf_len(), "BUILD_ID: 9, VERSION_STRING: 1.0".len());
+        assert_eq!(VERSIONS.path_len(0), 2);
+        assert_eq!(VERSIONS.path_len(1), 3);
+        assert_eq!(VersionTable::<2>::new(&[]).strbuf_len(), 0);
+        assert_eq!(VersionTable::<2>::new(&[]).path_len(0), 0);
+    }
+
+    /// Scans a synthetic module, `unreadable` chunk offsets fail to read
+    fn scan(module: &[u8], needle: &[u8], unreadable: &[u64]) -> Option<u64> {
+        return scan_chunks(module.len() as u64, needle, |offset, chunk| {
+            let start = offset as usize;
+            chunk.copy_from_slice(&module[start..start + chunk.len()]);
+            return !unreadable.contains(&offset);
+        });
+    }
+
+    /// Module of `len` bytes with `text` at `offset`
+    fn module_with(len: usize, offset: usize, text: &str) -> Vec<u8> {
+        let mut module = vec![0u8; len];
+        module[offset..offset + text.len()].copy_from_slice(text.as_bytes());
+        return module;
+    }
+
+    #[test]
+    fn finds_bytes() {
+        assert_eq!(find_bytes(b"xxBUILD_ID:BUILD_ID:", b"BUILD_ID:"), Some(2));
+        assert_eq!(find_bytes(b"xxBUILD_ID", b"BUILD_ID:"), None);
+        assert_eq!(find_bytes(b"BUILD", b"BUILD_ID:"), None);
+    }
+
+    #[test]
+    fn finds_build_ids_anywhere_in_the_module() {
+        let needle = BUILD_ID_PREFIX.as_bytes();
+        let len = 3 * SCAN_CHUNK_LEN + 100;
+        for offset in [0, 17, SCAN_CHUNK_LEN - needle.len(), SCAN_CHUNK_LEN - 4, SCAN_CHUNK_LEN, 2 * SCAN_CHUNK_LEN - 1, len - needle.len()] {
+            assert_eq!(scan(&module_with(len, offset, BUILD_ID_PREFIX), needle, &[]), Some(offset as u64), "offset 0x{:X}", offset);
+        }
+        assert_eq!(scan(&vec![0u8; len], needle, &[]), None);
+    }
+
+    #[test]
+    fn skips_unreadable_chunks() {
+        let needle = BUILD_ID_PREFIX.as_bytes();
+        let module = module_with(2 * SCAN_CHUNK_LEN, SCAN_CHUNK_LEN + 8, BUILD_ID_PREFIX);
+        assert_eq!(scan(&module, needle, &[0]), Some(SCAN_CHUNK_LEN as u64 + 8));
+        let module = module_with(2 * SCAN_CHUNK_LEN, 8, BUILD_ID_PREFIX);
+        assert_eq!(scan(&module, needle, &[0]), None);
+    }
+
+    #[test]
+    fn extracts_build_strings() {
+        let module = module_with(2 * SCAN_CHUNK_LEN, SCAN_CHUNK_LEN - 5, "BUILD_ID:1.0.4 2024-01-09\0unrelated");
+        let offset = scan(&module, BUILD_ID_PREFIX.as_bytes(), &[]).unwrap() as usize;
+        assert_eq!(text_until_nul(&module[offset..offset + 64]), "BUILD_ID:1.0.4 2024-01-09");
+        assert_eq!(text_until_nul(b"BUILD_ID:1.0.4"), "BUILD_ID:1.0.4");
+    }
+}
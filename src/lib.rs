@@ -4,28 +4,32 @@ pub mod autosplitter;
 pub mod game;
 
 use autosplitter::AutoSplitter;
-use game::{GameAutoSplitter, risk_of_rain, risk_of_rain_2, risk_of_rain_returns};
+use game::GameAutoSplitter;
 
 async_main!(stable);
 
 async fn main() {
+    asr::set_tick_rate(autosplitter::TICK_RATE);
     let mut autosplitter = AutoSplitter::new();
 
-    let mut ror1 = risk_of_rain::Game::new();
-    let mut ror2 = risk_of_rain_2::Game::new();
-    let mut rorr = risk_of_rain_returns::Game::new();
+    // same order as `game::supported_games()`
+    let mut games: Vec<Box<dyn GameAutoSplitter>> = Vec::new();
+    #[cfg(feature = "ror1")]
+    games.push(Box::new(game::risk_of_rain::Game::new()));
+    #[cfg(feature = "ror2")]
+    games.push(Box::new(game::risk_of_rain_2::Game::new()));
+    #[cfg(feature = "rorr")]
+    games.push(Box::new(game::risk_of_rain_returns::Game::new()));
 
     loop {
-        if let Some(process) = ror1.attach_any() {
-            process.until_closes(ror1.attached(&process, &mut autosplitter)).await;
-        } else if let Some(process) = ror2.attach_any() {
-            process.until_closes(ror2.attached(&process, &mut autosplitter)).await;
-        } else if let Some(process) = rorr.attach_any() {
-            process.until_closes(rorr.attached(&process, &mut autosplitter)).await;
+        let attached = autosplitter.attach_order(&games).into_iter()
+            .find_map(|game| games[game].attach_any().map(|process| (game, process)));
+
+        if let Some((game, process)) = attached {
+            process.until_closes(games[game].attached(&process, &mut autosplitter)).await;
         } else {
             autosplitter.update_loop(None);
         }
         next_tick().await;
     }
 }
-